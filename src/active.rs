@@ -0,0 +1,340 @@
+//! This module implements the unique active user tracking. Active users are counted in hourly [HyperLogLog] sketches,
+//! such that the memory stays bounded regardless of the size of the guild. The sketches can be persisted to a file to
+//! survive restarts.
+//!
+//! [HyperLogLog]: https://en.wikipedia.org/wiki/HyperLogLog
+
+use crate::persist;
+use serenity::all::UserId;
+use std::collections::VecDeque;
+use std::fs;
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// The number of index bits of a sketch. The standard error of the estimate is about `1.04 / sqrt(2^PRECISION)`.
+const PRECISION: u32 = 12;
+
+/// The number of registers of a sketch.
+const REGISTERS: usize = 1 << PRECISION;
+
+/// The length of a bucket (in seconds).
+const BUCKET_SECONDS: u64 = 60 * 60;
+
+/// The windows (and their names) that active users are counted over (in buckets).
+const WINDOWS: [(&str, u64); 4] = [("1h", 1), ("24h", 24), ("7d", 7 * 24), ("30d", 30 * 24)];
+
+/// The number of buckets that are kept (the largest window).
+const MAX_BUCKETS: u64 = 30 * 24;
+
+/// The magic bytes (and format version) of the persisted file.
+const MAGIC: &[u8; 8] = b"DCXAU001";
+
+/// Mixes the bits of a user id such that they are uniformly distributed (`SplitMix64` finalizer).
+fn hash(user_id: UserId) -> u64 {
+    let mut hash = user_id.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
+
+/// [`Sketch`] is a `HyperLogLog` sketch of user ids.
+#[derive(Clone, Debug)]
+struct Sketch {
+    registers: Vec<u8>,
+}
+
+impl Sketch {
+    /// Creates a new empty [`Sketch`].
+    fn new() -> Self {
+        Self {
+            registers: vec![0; REGISTERS],
+        }
+    }
+
+    /// Inserts a user into the sketch.
+    fn insert(&mut self, user_id: UserId) {
+        let hash = hash(user_id);
+        let index = usize::try_from(hash >> (64 - PRECISION)).expect("expected to fit in usize");
+        let rank = u8::try_from((hash << PRECISION).leading_zeros().min(64 - PRECISION) + 1)
+            .expect("expected to fit in u8");
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Merges another sketch into this sketch.
+    fn merge(&mut self, other: &Self) {
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Estimates the number of unique users of the sketch.
+    #[allow(clippy::cast_precision_loss)]
+    fn count(&self) -> f64 {
+        let registers = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / registers);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-i32::from(*register)))
+            .sum();
+        let estimate = alpha * registers * registers / sum;
+
+        // Use linear counting for small cardinalities
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|register| **register == 0)
+            .count();
+        if estimate <= 2.5 * registers && zeros > 0 {
+            registers * (registers / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+/// [`ActiveUsers`] tracks the unique active users within hourly buckets. Windows are counted over the most recent
+/// buckets (including the current one), such that they have an hourly resolution.
+#[derive(Debug)]
+pub struct ActiveUsers {
+    path: Option<PathBuf>,
+    buckets: VecDeque<(u64, Sketch)>,
+}
+
+impl ActiveUsers {
+    /// Creates a new [`ActiveUsers`] that is persisted to the file, if present. Previously persisted buckets are loaded
+    /// from the file, except for buckets in the future. If the file is invalid, the tracking starts empty.
+    pub fn new(path: Option<PathBuf>, now: u64) -> Self {
+        let mut buckets = match path.as_deref().map(read) {
+            None => VecDeque::new(),
+            Some(Ok(buckets)) => {
+                info!(buckets = buckets.len(), "Loaded active users");
+                buckets
+            }
+            Some(Err(why)) if why.kind() == ErrorKind::NotFound => VecDeque::new(),
+            Some(Err(why)) => {
+                warn!(err = %why, "Failed to load active users, starting empty");
+                VecDeque::new()
+            }
+        };
+        let bucket = now / BUCKET_SECONDS;
+        buckets.retain(|(start, _)| *start <= bucket);
+        Self { path, buckets }
+    }
+
+    /// Records the activity of a user.
+    pub fn insert(&mut self, user_id: UserId, now: u64) {
+        let bucket = now / BUCKET_SECONDS;
+        if self.buckets.back().is_none_or(|(last, _)| *last < bucket) {
+            self.buckets.push_back((bucket, Sketch::new()));
+        }
+        self.prune(bucket);
+        if let Some((_, sketch)) = self.buckets.back_mut() {
+            sketch.insert(user_id);
+        }
+    }
+
+    /// Estimates the number of unique active users per window.
+    pub fn counts(&mut self, now: u64) -> Vec<(&'static str, u64)> {
+        let bucket = now / BUCKET_SECONDS;
+        self.prune(bucket);
+        WINDOWS
+            .iter()
+            .map(|(name, length)| {
+                let mut merged = Sketch::new();
+                for (_, sketch) in self.buckets.iter().filter(|(start, _)| {
+                    *start <= bucket && bucket.saturating_sub(*start) < *length
+                }) {
+                    merged.merge(sketch);
+                }
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                (*name, merged.count().round() as u64)
+            })
+            .collect()
+    }
+
+    /// Persists the buckets to the file, if present. The buckets are encoded immediately, such that the returned future
+    /// does not borrow the tracking. Failures are only logged.
+    pub fn save(&self) -> impl Future<Output = ()> + 'static {
+        let snapshot = self.path.clone().map(|path| (path, encode(&self.buckets)));
+        async move {
+            let Some((path, data)) = snapshot else {
+                return;
+            };
+            if let Err(why) = persist::save(path, data).await {
+                warn!(err = %why, "Failed to save active users");
+            }
+        }
+    }
+
+    /// Drops all buckets that are no longer part of any window.
+    fn prune(&mut self, bucket: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|(start, _)| bucket.saturating_sub(*start) >= MAX_BUCKETS)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+/// Reads the buckets from a file.
+fn read(path: &Path) -> Result<VecDeque<(u64, Sketch)>, Error> {
+    let data = fs::read(path)?;
+    let Some(data) = data.strip_prefix(MAGIC) else {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "invalid active users file",
+        ));
+    };
+    let chunks = data.chunks_exact(8 + REGISTERS);
+    if !chunks.remainder().is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "truncated active users file",
+        ));
+    }
+    let buckets: VecDeque<(u64, Sketch)> = chunks
+        .map(|chunk| {
+            let (bucket, registers) = chunk.split_at(8);
+            let bucket = u64::from_le_bytes(bucket.try_into().expect("expected 8 bytes"));
+            let registers = registers.to_vec();
+            (bucket, Sketch { registers })
+        })
+        .collect();
+    if !buckets
+        .iter()
+        .zip(buckets.iter().skip(1))
+        .all(|((previous, _), (next, _))| previous < next)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "unordered active users file",
+        ));
+    }
+    Ok(buckets)
+}
+
+/// Encodes the buckets into the file format.
+fn encode(buckets: &VecDeque<(u64, Sketch)>) -> Vec<u8> {
+    let mut data = Vec::with_capacity(MAGIC.len() + buckets.len() * (8 + REGISTERS));
+    data.extend_from_slice(MAGIC);
+    for (bucket, sketch) in buckets {
+        data.extend_from_slice(&bucket.to_le_bytes());
+        data.extend_from_slice(&sketch.registers);
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The length of a day (in seconds).
+    const DAY: u64 = 24 * BUCKET_SECONDS;
+
+    fn count(counts: &[(&str, u64)], window: &str) -> u64 {
+        counts
+            .iter()
+            .find(|(name, _)| *name == window)
+            .map(|(_, count)| *count)
+            .expect("expected window")
+    }
+
+    #[test]
+    fn empty_sketch_counts_zero() {
+        assert!(Sketch::new().count().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn sketch_ignores_duplicates() {
+        let mut sketch = Sketch::new();
+        for _ in 0..10 {
+            sketch.insert(UserId::new(42));
+        }
+        assert!((sketch.count() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn sketch_estimates_cardinality() {
+        for users in [100, 10_000, 100_000] {
+            let mut sketch = Sketch::new();
+            for user_id in 1..=users {
+                sketch.insert(UserId::new(user_id));
+            }
+            let error = (sketch.count() - users as f64).abs() / users as f64;
+            assert!(error < 0.05, "error {error} for {users} users");
+        }
+    }
+
+    #[test]
+    fn sketch_merge_is_union() {
+        let mut first = Sketch::new();
+        let mut second = Sketch::new();
+        for user_id in 1..=1_000 {
+            first.insert(UserId::new(user_id));
+            second.insert(UserId::new(user_id + 500));
+        }
+        first.merge(&second);
+        assert!((first.count() - 1_500.0).abs() < 75.0);
+    }
+
+    #[test]
+    fn counts_windows() {
+        let mut active = ActiveUsers::new(None, 0);
+        let now = 40 * DAY;
+        active.insert(UserId::new(1), now - 35 * DAY);
+        active.insert(UserId::new(2), now - 10 * DAY);
+        active.insert(UserId::new(3), now - 2 * DAY);
+        active.insert(UserId::new(4), now - 2 * BUCKET_SECONDS);
+        active.insert(UserId::new(5), now);
+        let counts = active.counts(now);
+        assert_eq!(count(&counts, "1h"), 1);
+        assert_eq!(count(&counts, "24h"), 2);
+        assert_eq!(count(&counts, "7d"), 3);
+        assert_eq!(count(&counts, "30d"), 4);
+        assert_eq!(active.buckets.len(), 4);
+    }
+
+    #[test]
+    fn counts_skip_future_buckets() {
+        let mut active = ActiveUsers::new(None, 0);
+        active.insert(UserId::new(1), 10 * DAY);
+        let counts = active.counts(DAY);
+        assert!(counts.iter().all(|(_, count)| *count == 0));
+    }
+
+    #[test]
+    fn load_drops_future_buckets() {
+        let path = std::env::temp_dir().join(format!("active-users-{}.bin", std::process::id()));
+        let mut buckets = VecDeque::new();
+        for bucket in [1, 2, 50] {
+            let mut sketch = Sketch::new();
+            sketch.insert(UserId::new(bucket));
+            buckets.push_back((bucket, sketch));
+        }
+        persist::write(&path, &encode(&buckets)).expect("expected to write");
+        let active = ActiveUsers::new(Some(path.clone()), 10 * BUCKET_SECONDS);
+        fs::remove_file(&path).expect("expected to remove");
+        let starts: Vec<u64> = active.buckets.iter().map(|(start, _)| *start).collect();
+        assert_eq!(starts, vec![1, 2]);
+    }
+
+    #[test]
+    fn read_rejects_unordered_buckets() {
+        let path =
+            std::env::temp_dir().join(format!("active-users-{}.unordered", std::process::id()));
+        let buckets = VecDeque::from([(2, Sketch::new()), (1, Sketch::new())]);
+        persist::write(&path, &encode(&buckets)).expect("expected to write");
+        let result = read(&path);
+        fs::remove_file(&path).expect("expected to remove");
+        assert_eq!(
+            result.expect_err("expected to fail").kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
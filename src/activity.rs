@@ -0,0 +1,172 @@
+//! This module implements the activity session tracking. A session lasts from the first appearance of an activity in
+//! the presence of a member until it disappears.
+
+use crate::metrics::ActivityLabels;
+use serenity::all::UserId;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Gets the current time in milliseconds since the Unix epoch (the resolution of activity timestamps).
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| {
+            duration.as_millis().try_into().unwrap_or(u64::MAX)
+        })
+}
+
+/// Converts a duration in milliseconds into seconds.
+#[allow(clippy::cast_precision_loss)]
+fn seconds(millis: u64) -> f64 {
+    millis as f64 / 1000.0
+}
+
+/// [`ActivitySessions`] tracks the open activity sessions of all members. Each session remembers when it was last
+/// flushed, such that its time is only counted once. Members remember when their sessions last ended, such that
+/// reappearing activities (with their original start timestamp) are not counted twice.
+#[derive(Debug)]
+pub struct ActivitySessions {
+    tracked_since: u64,
+    sessions: HashMap<UserId, HashMap<ActivityLabels, u64>>,
+    ended: HashMap<UserId, u64>,
+}
+
+impl ActivitySessions {
+    /// Creates a new [`ActivitySessions`] that counts time from now on.
+    pub fn new(now: u64) -> Self {
+        Self {
+            tracked_since: now,
+            sessions: HashMap::new(),
+            ended: HashMap::new(),
+        }
+    }
+
+    /// Replaces the activities of a member. Sessions of new activities start at their start timestamp (bounded by the
+    /// start of the tracking and the last ended session of the member), if present. Returns the remaining seconds of
+    /// the ended sessions.
+    pub fn update(
+        &mut self,
+        user_id: UserId,
+        activities: impl IntoIterator<Item = (ActivityLabels, Option<u64>)>,
+        now: u64,
+    ) -> Vec<(ActivityLabels, f64)> {
+        let mut old = self.sessions.remove(&user_id).unwrap_or_default();
+        let since = self
+            .ended
+            .get(&user_id)
+            .map_or(self.tracked_since, |ended| self.tracked_since.max(*ended))
+            .min(now);
+        let mut new = HashMap::new();
+        for (labels, start) in activities {
+            let flushed = old
+                .remove(&labels)
+                .unwrap_or_else(|| start.unwrap_or(now).clamp(since, now));
+            new.insert(labels, flushed);
+        }
+        if !new.is_empty() {
+            self.sessions.insert(user_id, new);
+        }
+        if !old.is_empty() {
+            self.ended.insert(user_id, now);
+        }
+
+        old.into_iter()
+            .map(|(labels, flushed)| (labels, seconds(now.saturating_sub(flushed))))
+            .collect()
+    }
+
+    /// Ends all sessions of a member (that left the guild). Returns the remaining seconds of the ended sessions.
+    pub fn end(&mut self, user_id: UserId, now: u64) -> Vec<(ActivityLabels, f64)> {
+        let seconds = self.update(user_id, [], now);
+        self.ended.remove(&user_id);
+        seconds
+    }
+
+    /// Takes the seconds of all open sessions that passed since their last flush.
+    pub fn flush(&mut self, now: u64) -> Vec<(ActivityLabels, f64)> {
+        self.sessions
+            .values_mut()
+            .flat_map(HashMap::iter_mut)
+            .map(|(labels, flushed)| {
+                let elapsed = now.saturating_sub(*flushed);
+                *flushed = now;
+                (labels.clone(), seconds(elapsed))
+            })
+            .collect()
+    }
+
+    /// Clears all sessions. The time is counted from now on.
+    pub fn clear(&mut self, now: u64) {
+        self.tracked_since = now;
+        self.sessions.clear();
+        self.ended.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(name: &str) -> ActivityLabels {
+        ActivityLabels {
+            activity_application_id: None,
+            activity_name: name.to_string(),
+            activity_type: "playing".to_string(),
+        }
+    }
+
+    #[test]
+    fn session_is_counted_until_it_ends() {
+        let mut sessions = ActivitySessions::new(0);
+        let user_id = UserId::new(1);
+        assert!(
+            sessions
+                .update(user_id, [(labels("a"), None)], 1_000)
+                .is_empty()
+        );
+        assert_eq!(
+            sessions.update(user_id, [], 4_000),
+            vec![(labels("a"), 3.0)]
+        );
+        assert!(sessions.flush(5_000).is_empty());
+    }
+
+    #[test]
+    fn session_starts_at_timestamp_bounded_by_tracking() {
+        let mut sessions = ActivitySessions::new(10_000);
+        let user_id = UserId::new(1);
+        sessions.update(user_id, [(labels("a"), Some(2_000))], 12_000);
+        sessions.update(
+            user_id,
+            [(labels("a"), Some(2_000)), (labels("b"), Some(11_000))],
+            12_000,
+        );
+        let mut seconds = sessions.flush(13_000);
+        seconds.sort_by(|(a, _), (b, _)| a.activity_name.cmp(&b.activity_name));
+        assert_eq!(seconds, vec![(labels("a"), 3.0), (labels("b"), 2.0)]);
+    }
+
+    #[test]
+    fn reappearing_session_is_not_counted_twice() {
+        let mut sessions = ActivitySessions::new(0);
+        let user_id = UserId::new(1);
+        sessions.update(user_id, [(labels("a"), Some(1_000))], 2_000);
+        assert_eq!(
+            sessions.update(user_id, [], 5_000),
+            vec![(labels("a"), 4.0)]
+        );
+        sessions.update(user_id, [(labels("a"), Some(1_000))], 6_000);
+        assert_eq!(sessions.flush(8_000), vec![(labels("a"), 3.0)]);
+    }
+
+    #[test]
+    fn flush_counts_time_once() {
+        let mut sessions = ActivitySessions::new(0);
+        let user_id = UserId::new(1);
+        sessions.update(user_id, [(labels("a"), None)], 0);
+        assert_eq!(sessions.flush(2_000), vec![(labels("a"), 2.0)]);
+        assert_eq!(sessions.flush(3_000), vec![(labels("a"), 1.0)]);
+        assert_eq!(sessions.end(user_id, 3_500), vec![(labels("a"), 0.5)]);
+        assert!(sessions.flush(4_000).is_empty());
+    }
+}
@@ -0,0 +1,54 @@
+//! This module implements the extension point for custom metrics. Collectors are driven by the same Discord gateway
+//! events as the built-in metrics.
+
+use prometheus_client::registry::Registry;
+use serenity::all::{Context, FullEvent, Guild};
+use serenity::async_trait;
+use serenity::framework::Framework;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// [`Collector`] is a custom bundle of metrics that is maintained alongside the built-in metrics of the exporter.
+///
+/// Collectors register their metrics in the shared [`Registry`] once on startup and are then notified about every
+/// Discord gateway event. They are registered through the [`Builder`](crate::Builder).
+#[async_trait]
+pub trait Collector: Send + Sync {
+    /// Registers the metrics of the collector in the shared [`Registry`] of the [metrics handler](crate::metrics).
+    fn register(&self, registry: &mut Registry);
+
+    /// Handles the creation (or re-creation) of a guild. It is called before [`Collector::event`] is invoked for the
+    /// same event, such that the collector can (re-)initialize its metrics.
+    async fn guild_create(&self, _ctx: &Context, _guild: &Guild) {}
+
+    /// Handles any Discord gateway event, including the guild creation.
+    async fn event(&self, _ctx: &Context, _event: &FullEvent) {}
+
+    /// Runs the background work of the collector. The task is managed by the application and should return once the
+    /// [`CancellationToken`] is cancelled. By default, the collector has no background work.
+    async fn run(&self, _shutdown: CancellationToken) {}
+}
+
+/// [`Dispatcher`] forwards the Discord gateway events to all registered [`Collector`]s.
+pub struct Dispatcher {
+    collectors: Vec<Arc<dyn Collector>>,
+}
+
+impl Dispatcher {
+    /// Creates a new [`Dispatcher`] for a list of [`Collector`]s.
+    pub fn new(collectors: Vec<Arc<dyn Collector>>) -> Self {
+        Self { collectors }
+    }
+}
+
+#[async_trait]
+impl Framework for Dispatcher {
+    async fn dispatch(&self, ctx: Context, event: FullEvent) {
+        for collector in &self.collectors {
+            if let FullEvent::GuildCreate { guild, .. } = &event {
+                collector.guild_create(&ctx, guild).await;
+            }
+            collector.event(&ctx, &event).await;
+        }
+    }
+}
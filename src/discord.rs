@@ -1,22 +1,59 @@
 //! This module implements the Discord guild listener. Any updates are applied to the metrics handler.
 
+use crate::active::ActiveUsers;
+use crate::activity::{ActivitySessions, now_millis};
+use crate::collector::{Collector, Dispatcher};
+use crate::emoji::{ReactionCache, ReactionEmoji, unicode_emoji_name};
+use crate::last_seen::LastSeen;
 use crate::metrics;
 use crate::metrics::{
-    ActivityLabels, BoostLabels, BotLabels, ChannelLabels, EmoteUsedLabels, GuildsLabels,
-    MemberLabels, MemberStatusLabels, MemberVoiceLabels, MessageSentLabels,
+    ActiveUsersLabels, ActivityLabels, AuditLogEntryLabels, AutomodActionLabels, AutomodRuleLabels,
+    BoostLabels, BotLabels, BotMessageSentLabels, ChannelLabels, EmoteRemovedLabels,
+    EmoteUsedLabels, GuildsLabels, MemberBannedLabels, MemberClientStatusLabels,
+    MemberInactiveLabels, MemberJoinedLabels, MemberJoinedYoungLabels, MemberKickedLabels,
+    MemberLabels, MemberLeftLabels, MemberOnlinePeakLabels, MemberStatusLabels, MemberTenureLabels,
+    MemberTimedOutLabels, MemberTimeoutLabels, MemberUnbannedLabels, MemberVoiceLabels,
+    MessageAttachmentTypeLabels, MessageDeletedLabels, MessageEditedLabels, MessageKindLabels,
+    MessageMatchedLabels, MessageMentionsLabels, MessageSentLabels, MessageShapeLabels,
+    ModeratorActionLabels, RaidSuspectedLabels, ReactionActiveLabels, ReactionClearedLabels,
+    RoleLabels, RoleMemberLabels, StageInstanceLabels, StatusTransitionLabels,
+    VoiceChannelCapacityLabels, VoiceSecondsLabels, VoiceSessionDurationLabels,
 };
+use crate::patterns::Patterns;
+use crate::peak::OnlinePeaks;
+use crate::raid;
+use crate::raid::{RaidAlert, RaidDetector};
+use crate::voice::VoiceSession;
 use serenity::all::{
-    ChannelId, Context, EventHandler, GatewayIntents, Guild, GuildChannel, GuildId, Member,
-    Message, PartialGuild, Presence, Reaction, ReactionType, UnavailableGuild, User, UserId,
-    VoiceState, parse_emoji,
+    Activity, ActivityType, ApplicationId, AuditLogEntry, ChannelId, ChannelType, Context,
+    EventHandler, GatewayIntents, Guild, GuildChannel, GuildId, GuildMemberUpdateEvent, Member,
+    Message, MessageId, MessageType, MessageUpdateEvent, OnlineStatus, PartialGuild, Presence,
+    Reaction, Role, RoleId, RuleId, StageInstance, StageInstanceId, Timestamp, UnavailableGuild,
+    User, UserId, VoiceState, WebhookId, parse_emoji,
 };
+use serenity::model::guild::audit_log::{Action, Change, MemberAction};
+use serenity::model::guild::automod::{ActionExecution, Rule};
 use serenity::{Client, async_trait};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::select;
 use tokio::sync::RwLock;
+use tokio::time::interval;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, warn};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The interval in which the time-based metrics are refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The interval in which the active users and last seen members are persisted (if configured).
+const SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The default length of the rolling window of the raid detection.
+const DEFAULT_RAID_WINDOW: Duration = Duration::from_secs(60);
 
 /// [`CachedUser`] is a bundle of information that should be cached. This cache is complementary to the
 /// build-in serenity cache. It contains information required to decrement the prometheus gauges.
@@ -25,27 +62,722 @@ pub struct CachedUser {
     presence: Presence,
 }
 
+/// [`MessageShape`] is an optional breakdown of the shape of messages. Each shape enables its own metrics.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum MessageShape {
+    /// The content length in characters (`message_length`).
+    Length,
+    /// The number of attachments (`message_attachments`).
+    Attachments,
+    /// The content type category of attachments (`message_attachment_type`).
+    AttachmentType,
+    /// The number of embeds (`message_embeds`).
+    Embeds,
+    /// The number of user, role and everyone mentions (`message_mentions`).
+    Mentions,
+    /// Whether the message is a reply (`message_reply`).
+    Reply,
+}
+
+impl FromStr for MessageShape {
+    type Err = String;
+
+    /// Parses a [`MessageShape`] from its kebab-case name (e.g. `attachment-type`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "length" => Ok(Self::Length),
+            "attachments" => Ok(Self::Attachments),
+            "attachment-type" => Ok(Self::AttachmentType),
+            "embeds" => Ok(Self::Embeds),
+            "mentions" => Ok(Self::Mentions),
+            "reply" => Ok(Self::Reply),
+            _ => Err(format!(
+                "invalid message shape (expected length, attachments, attachment-type, embeds, mentions or reply): {s}"
+            )),
+        }
+    }
+}
+
+/// [`Config`] is the configuration of the optional metrics of the [`Handler`].
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The number of recently reacted messages whose active reactions are tracked. Disabled if zero.
+    pub reaction_cache_size: usize,
+    /// The enabled message shape metrics.
+    pub message_shapes: HashSet<MessageShape>,
+    /// Whether messages of system users (e.g. official Discord announcements) are tracked.
+    pub system_messages: bool,
+    /// The bot, webhook or application ids whose messages are tracked. Disabled if empty.
+    pub bot_allowlist: HashSet<u64>,
+    /// The number of member joins within the raid window at which a raid is suspected. Disabled if zero.
+    pub raid_threshold: usize,
+    /// The length of the rolling window in which member joins are counted for the raid detection.
+    pub raid_window: Duration,
+    /// The URL that raid alerts are posted to. Disabled if absent.
+    pub raid_webhook: Option<String>,
+    /// The maximum age (in days) of accounts that are counted as young on join. Disabled if zero.
+    pub young_account_days: u64,
+    /// The tier role ids in descending order. Members are labelled with their highest tier role. Disabled if empty.
+    pub tier_roles: Vec<u64>,
+    /// Whether members in the AFK channel of the guild are excluded from the `member_voice` metric.
+    pub exclude_afk: bool,
+    /// Whether custom statuses are excluded from the `activity` metric.
+    pub exclude_custom_status: bool,
+    /// The aliases (activity name to alias) that activity names are merged through.
+    pub activity_aliases: HashMap<String, String>,
+    /// The file that the active users are persisted to. Disabled if absent.
+    pub active_users_file: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            reaction_cache_size: 0,
+            message_shapes: HashSet::new(),
+            system_messages: false,
+            bot_allowlist: HashSet::new(),
+            raid_threshold: 0,
+            raid_window: DEFAULT_RAID_WINDOW,
+            raid_webhook: None,
+            young_account_days: 0,
+            tier_roles: Vec::new(),
+            exclude_afk: false,
+            exclude_custom_status: false,
+            activity_aliases: HashMap::new(),
+            active_users_file: None,
+        }
+    }
+}
+
 /// [`Handler`] is the [servable](serve) Discord listener. It listens for Discord gateway events and
 /// updates the [metrics](metrics::Handler) accordingly.
 pub struct Handler {
     metrics_handler: Arc<metrics::Handler>,
+    patterns: Option<Arc<Patterns>>,
+    last_seen: Option<Arc<LastSeen>>,
+    config: Config,
     created: RwLock<bool>,
     users: RwLock<HashMap<UserId, CachedUser>>,
+    reactions: RwLock<ReactionCache>,
+    raid: RwLock<RaidDetector>,
+    timeouts: RwLock<HashMap<UserId, Timestamp>>,
+    automod_rules: RwLock<HashMap<RuleId, Rule>>,
+    member_roles: RwLock<HashMap<UserId, Vec<RoleId>>>,
+    voice_sessions: RwLock<HashMap<UserId, VoiceSession>>,
+    activity_sessions: RwLock<ActivitySessions>,
+    online_peaks: RwLock<OnlinePeaks>,
+    stage_instances: RwLock<HashMap<StageInstanceId, StageInstanceLabels>>,
+    active_users: RwLock<ActiveUsers>,
+    http: reqwest::Client,
 }
 
 impl Handler {
-    /// Creates a new [`Handler`] for a [`metrics::Handler`]. Any updates are applied to these metrics.
-    pub fn new(metrics_handler: Arc<metrics::Handler>) -> Self {
+    /// Creates a new [`Handler`] for a [`metrics::Handler`]. Any updates are applied to these metrics. Messages are
+    /// additionally matched against the [`Patterns`], if present. The last activity of members is recorded in the
+    /// [`LastSeen`] store, if present. The optional metrics are enabled by the [`Config`].
+    pub fn new(
+        metrics_handler: Arc<metrics::Handler>,
+        patterns: Option<Arc<Patterns>>,
+        last_seen: Option<Arc<LastSeen>>,
+        config: Config,
+    ) -> Self {
         Self {
             metrics_handler,
+            patterns,
+            last_seen,
+            reactions: RwLock::new(ReactionCache::new(config.reaction_cache_size)),
+            raid: RwLock::new(RaidDetector::new(config.raid_threshold, config.raid_window)),
+            timeouts: RwLock::new(HashMap::new()),
+            automod_rules: RwLock::new(HashMap::new()),
+            member_roles: RwLock::new(HashMap::new()),
+            voice_sessions: RwLock::new(HashMap::new()),
+            activity_sessions: RwLock::new(ActivitySessions::new(now_millis())),
+            online_peaks: RwLock::new(OnlinePeaks::new()),
+            stage_instances: RwLock::new(HashMap::new()),
+            active_users: RwLock::new(ActiveUsers::new(
+                config.active_users_file.clone(),
+                now_millis() / 1000,
+            )),
+            http: reqwest::Client::builder()
+                .timeout(raid::ALERT_TIMEOUT)
+                .build()
+                .expect("failed to build http client"),
+            config,
             created: RwLock::new(false),
             users: RwLock::new(HashMap::new()),
         }
     }
+
+    /// Runs the periodic refresh of the time-based metrics. The task should return once the [`CancellationToken`] is
+    /// cancelled.
+    #[instrument(skip(self, shutdown))]
+    pub async fn run(&self, shutdown: CancellationToken) {
+        let mut refresh_interval = interval(REFRESH_INTERVAL);
+        let mut save_interval = interval(SAVE_INTERVAL);
+        loop {
+            select! {
+                _ = refresh_interval.tick() => self.refresh().await,
+                _ = save_interval.tick() => self.save().await,
+                () = shutdown.cancelled() => break,
+            }
+        }
+
+        // Handle `voice_seconds` metric (open sessions are not observed as they did not end)
+        let now = Instant::now();
+        for session in self.voice_sessions.write().await.values_mut() {
+            self.flush_voice_session(session, now);
+        }
+
+        // Handle `activity_seconds` metric
+        let seconds = self.activity_sessions.write().await.flush(now_millis());
+        self.add_activity_seconds(seconds);
+
+        // Persist active users and last seen members
+        self.save().await;
+    }
+
+    /// Persists the active users and last seen members (if configured).
+    async fn save(&self) {
+        let save = self.active_users.read().await.save();
+        save.await;
+        if let Some(last_seen) = &self.last_seen {
+            last_seen.save().await;
+        }
+    }
+
+    /// Refreshes the time-based metrics.
+    async fn refresh(&self) {
+        // Handle `voice_seconds` metric
+        let now = Instant::now();
+        for session in self.voice_sessions.write().await.values_mut() {
+            self.flush_voice_session(session, now);
+        }
+
+        // Handle `activity_seconds` metric
+        let seconds = self.activity_sessions.write().await.flush(now_millis());
+        self.add_activity_seconds(seconds);
+
+        // Handle `member_online_peak` metric
+        let peaks = self.online_peaks.write().await.sample(Instant::now());
+        self.set_online_peaks(peaks);
+
+        // Handle `active_users` metric
+        let counts = self.active_users.write().await.counts(now_millis() / 1000);
+        for (window, count) in counts {
+            self.metrics_handler
+                .active_users
+                .get_or_create(&ActiveUsersLabels::new(window))
+                .set(count.try_into().expect("expected to fit in i64"));
+        }
+
+        // Handle `member_inactive` metric
+        if let Some(last_seen) = &self.last_seen {
+            for (days, count) in last_seen.counts(now_millis() / 1000).await {
+                self.metrics_handler
+                    .member_inactive
+                    .get_or_create(&MemberInactiveLabels::new(days))
+                    .set(count.try_into().expect("expected to fit in i64"));
+            }
+        }
+
+        // Handle `raid_suspected` metric
+        let state = self.raid.write().await.refresh(Instant::now());
+        self.update_raid(state).await;
+
+        // Handle `member_timed_out` metric
+        let mut timeouts = self.timeouts.write().await;
+        timeouts.retain(|_, until| active_timeout(Some(*until)).is_some());
+        self.update_timed_out(timeouts.len());
+    }
+
+    /// Moves a member to a new voice channel (or none). The previous voice session is ended, if the channel changed.
+    async fn update_voice_session(
+        &self,
+        user_id: UserId,
+        channel: Option<(Option<ChannelId>, ChannelId)>,
+        now: Instant,
+    ) {
+        let mut sessions = self.voice_sessions.write().await;
+        let channel_id = channel.map(|(_, channel_id)| channel_id);
+        if sessions.get(&user_id).map(|session| session.channel_id) == channel_id {
+            return;
+        }
+
+        if let Some(mut session) = sessions.remove(&user_id) {
+            self.flush_voice_session(&mut session, now);
+
+            // Handle `voice_session_duration_seconds` metric
+            self.metrics_handler
+                .voice_session_duration_seconds
+                .get_or_create(&VoiceSessionDurationLabels::new())
+                .observe(session.duration(now));
+        }
+        if let Some((category_id, channel_id)) = channel {
+            sessions.insert(user_id, VoiceSession::new(category_id, channel_id, now));
+        }
+    }
+
+    /// Applies the time of a voice session since its last flush to the `voice_seconds` metric.
+    fn flush_voice_session(&self, session: &mut VoiceSession, now: Instant) {
+        self.metrics_handler
+            .voice_seconds
+            .get_or_create(&VoiceSecondsLabels::new(
+                session.category_id,
+                session.channel_id,
+            ))
+            .inc_by(session.flush(now));
+    }
+
+    /// Applies a change of the roles of a member to the `role_member` metric.
+    fn update_member_roles(&self, old: &[RoleId], new: &[RoleId]) {
+        for role_id in old.iter().filter(|role_id| !new.contains(role_id)) {
+            self.metrics_handler
+                .role_member
+                .get_or_create(&RoleMemberLabels::new(*role_id))
+                .dec();
+        }
+        for role_id in new.iter().filter(|role_id| !old.contains(role_id)) {
+            self.metrics_handler
+                .role_member
+                .get_or_create(&RoleMemberLabels::new(*role_id))
+                .inc();
+        }
+    }
+
+    /// Gets the highest tier role of a set of roles.
+    fn tier_role(&self, roles: &[RoleId]) -> Option<RoleId> {
+        self.config
+            .tier_roles
+            .iter()
+            .map(|role_id| RoleId::new(*role_id))
+            .find(|role_id| roles.contains(role_id))
+    }
+
+    /// Gets the highest tier role of a member from the member roles cache.
+    async fn member_tier_role(&self, user_id: UserId) -> Option<RoleId> {
+        if self.config.tier_roles.is_empty() {
+            return None;
+        }
+        self.member_roles
+            .read()
+            .await
+            .get(&user_id)
+            .and_then(|roles| self.tier_role(roles))
+    }
+
+    /// Moves the gauges of a member from the old to the new tier role.
+    async fn update_tier_role(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        user_id: UserId,
+        old: Option<RoleId>,
+        new: Option<RoleId>,
+    ) {
+        // Handle `member_status` metric
+        if let Some(cached_user) = self.users.read().await.get(&user_id) {
+            let status = cached_user.presence.status;
+            self.metrics_handler
+                .member_status
+                .get_or_create(&MemberStatusLabels::new(status, old))
+                .dec();
+            self.metrics_handler
+                .member_status
+                .get_or_create(&MemberStatusLabels::new(status, new))
+                .inc();
+        }
+
+        // Handle `member_voice` metric
+        let voice = ctx
+            .cache
+            .guild(guild_id)
+            .and_then(|guild| guild.voice_states.get(&user_id).cloned());
+        if let Some(voice) = voice {
+            if let Some(channel_id) = voice
+                .channel_id
+                .filter(|channel_id| !self.is_afk_excluded(ctx, guild_id, *channel_id))
+            {
+                let (category_id, channel_id) = category_channel(ctx, guild_id, channel_id);
+                self.metrics_handler
+                    .member_voice
+                    .get_or_create(&MemberVoiceLabels::new(
+                        category_id,
+                        channel_id,
+                        &voice,
+                        is_stage(ctx, guild_id, channel_id),
+                        old,
+                    ))
+                    .dec();
+                self.metrics_handler
+                    .member_voice
+                    .get_or_create(&MemberVoiceLabels::new(
+                        category_id,
+                        channel_id,
+                        &voice,
+                        is_stage(ctx, guild_id, channel_id),
+                        new,
+                    ))
+                    .inc();
+            }
+        }
+    }
+
+    /// Checks whether a voice channel is the AFK channel of the guild and AFK channels are excluded from the
+    /// `member_voice` metric. It expects the guild to be cached.
+    fn is_afk_excluded(&self, ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> bool {
+        self.config.exclude_afk
+            && ctx
+                .cache
+                .guild(guild_id)
+                .and_then(|guild| guild.afk_metadata.as_ref().map(|afk| afk.afk_channel_id))
+                .is_some_and(|afk_channel_id| afk_channel_id == channel_id)
+    }
+
+    /// Moves the members in the old and new AFK channel in or out of the `member_voice` metric after the AFK channel of
+    /// the guild changed. It expects the cache to already contain the new AFK channel.
+    async fn update_afk_voice(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        old_afk_channel_id: Option<ChannelId>,
+        new_afk_channel_id: Option<ChannelId>,
+    ) {
+        let voice_states: Vec<VoiceState> = ctx
+            .cache
+            .guild(guild_id)
+            .map(|guild| guild.voice_states.values().cloned().collect())
+            .unwrap_or_default();
+        for voice in voice_states {
+            let Some(channel_id) = voice.channel_id else {
+                continue;
+            };
+            let included = if Some(channel_id) == old_afk_channel_id {
+                true
+            } else if Some(channel_id) == new_afk_channel_id {
+                false
+            } else {
+                continue;
+            };
+
+            let (category_id, channel_id) = category_channel(ctx, guild_id, channel_id);
+            let tier_role_id = self.member_tier_role(voice.user_id).await;
+            let gauge = self
+                .metrics_handler
+                .member_voice
+                .get_or_create(&MemberVoiceLabels::new(
+                    category_id,
+                    channel_id,
+                    &voice,
+                    is_stage(ctx, guild_id, channel_id),
+                    tier_role_id,
+                ));
+            if included {
+                gauge.inc();
+            } else {
+                gauge.dec();
+            }
+        }
+    }
+
+    /// Gets the tracked activities of a presence. Custom statuses are skipped if they are excluded.
+    fn activities<'a>(&self, presence: &'a Presence) -> impl Iterator<Item = &'a Activity> {
+        let exclude_custom_status = self.config.exclude_custom_status;
+        presence.activities.iter().filter(move |activity| {
+            !(exclude_custom_status && activity.kind == ActivityType::Custom)
+        })
+    }
+
+    /// Replaces the activity sessions of a member with the tracked activities of the presence.
+    async fn update_activity_sessions(&self, presence: &Presence) {
+        let activities: Vec<_> = self
+            .activities(presence)
+            .map(|activity| {
+                (
+                    ActivityLabels::new(activity, &self.config.activity_aliases),
+                    activity.timestamps.as_ref().and_then(|times| times.start),
+                )
+            })
+            .collect();
+        let seconds =
+            self.activity_sessions
+                .write()
+                .await
+                .update(presence.user.id, activities, now_millis());
+        self.add_activity_seconds(seconds);
+    }
+
+    /// Applies the seconds of activity sessions to the `activity_seconds` metric.
+    fn add_activity_seconds(&self, seconds: Vec<(ActivityLabels, f64)>) {
+        for (labels, seconds) in seconds {
+            self.metrics_handler
+                .activity_seconds
+                .get_or_create(&labels)
+                .inc_by(seconds);
+        }
+    }
+
+    /// Applies the status of a member to the `member_online_peak` metric.
+    async fn update_online_peaks(&self, user_id: UserId, is_online: bool) {
+        let peaks = self
+            .online_peaks
+            .write()
+            .await
+            .update(Instant::now(), user_id, is_online);
+        self.set_online_peaks(peaks);
+    }
+
+    /// Applies the peaks of online members per window to the `member_online_peak` metric.
+    fn set_online_peaks(&self, peaks: Vec<(&'static str, u64)>) {
+        for (window, peak) in peaks {
+            self.metrics_handler
+                .member_online_peak
+                .get_or_create(&MemberOnlinePeakLabels::new(window))
+                .set(peak.try_into().expect("expected to fit in i64"));
+        }
+    }
+
+    /// Records the activity of a member for the `active_users` metric.
+    async fn record_active_user(&self, user_id: UserId) {
+        self.active_users
+            .write()
+            .await
+            .insert(user_id, now_millis() / 1000);
+    }
+
+    /// Records the activity of a member for the `member_inactive` metric, if enabled. Bots are skipped.
+    async fn touch_last_seen(&self, ctx: &Context, user_id: UserId) {
+        if let Some(last_seen) = &self.last_seen {
+            if !is_bot(ctx, Some(user_id)) {
+                last_seen.touch(user_id, now_millis() / 1000).await;
+            }
+        }
+    }
+
+    /// Applies a created or updated stage instance to the `stage_instance` metric. The labels are stored, such that the
+    /// series can be removed even after the channel was deleted.
+    async fn update_stage_instance(&self, ctx: &Context, stage_instance: &StageInstance) {
+        let (category_id, channel_id) =
+            find_category_channel(ctx, stage_instance.guild_id, stage_instance.channel_id)
+                .unwrap_or((None, stage_instance.channel_id));
+        let labels = StageInstanceLabels::new(category_id, channel_id);
+        let old = self
+            .stage_instances
+            .write()
+            .await
+            .insert(stage_instance.id, labels.clone());
+        if let Some(old) = old.filter(|old| *old != labels) {
+            self.metrics_handler.stage_instance.remove(&old);
+        }
+        self.metrics_handler
+            .stage_instance
+            .get_or_create(&labels)
+            .set(1);
+    }
+
+    /// Applies the number of active timeouts to the `member_timed_out` metric.
+    fn update_timed_out(&self, timeouts: usize) {
+        self.metrics_handler
+            .member_timed_out
+            .get_or_create(&MemberTimedOutLabels::new())
+            .set(timeouts.try_into().expect("expected to fit in i64"));
+    }
+
+    /// Applies a change of the raid state to the `raid_suspected` metric and posts the alert, if configured.
+    async fn update_raid(&self, state: Option<bool>) {
+        let Some(suspected) = state else {
+            return;
+        };
+        let (joins, window) = {
+            let raid = self.raid.read().await;
+            (raid.joins(), raid.window())
+        };
+        if suspected {
+            warn!(joins, "Raid suspected");
+        } else {
+            info!(joins, "Raid no longer suspected");
+        }
+
+        self.metrics_handler
+            .raid_suspected
+            .get_or_create(&RaidSuspectedLabels::new())
+            .set(i64::from(suspected));
+
+        if let Some(url) = &self.config.raid_webhook {
+            let alert = RaidAlert {
+                suspected,
+                joins,
+                window_seconds: window.as_secs(),
+            };
+            raid::alert(&self.http, url, &alert).await;
+        }
+    }
+
+    /// Observes the enabled [message shape](MessageShape) metrics of a message.
+    #[allow(clippy::cast_precision_loss)]
+    fn observe_message_shape(
+        &self,
+        msg: &Message,
+        category_id: Option<ChannelId>,
+        channel_id: ChannelId,
+    ) {
+        let shapes = &self.config.message_shapes;
+        let labels = MessageShapeLabels::new(category_id, channel_id);
+
+        // Handle `message_length` metric
+        if shapes.contains(&MessageShape::Length) {
+            self.metrics_handler
+                .message_length
+                .get_or_create(&labels)
+                .observe(msg.content.chars().count() as f64);
+        }
+
+        // Handle `message_attachments` metric
+        if shapes.contains(&MessageShape::Attachments) {
+            self.metrics_handler
+                .message_attachments
+                .get_or_create(&labels)
+                .observe(msg.attachments.len() as f64);
+        }
+
+        // Handle `message_attachment_type` metric
+        if shapes.contains(&MessageShape::AttachmentType) {
+            for attachment in &msg.attachments {
+                self.metrics_handler
+                    .message_attachment_type
+                    .get_or_create(&MessageAttachmentTypeLabels::new(
+                        category_id,
+                        channel_id,
+                        attachment.content_type.as_deref(),
+                    ))
+                    .inc();
+            }
+        }
+
+        // Handle `message_embeds` metric
+        if shapes.contains(&MessageShape::Embeds) {
+            self.metrics_handler
+                .message_embeds
+                .get_or_create(&labels)
+                .observe(msg.embeds.len() as f64);
+        }
+
+        // Handle `message_mentions` metric
+        if shapes.contains(&MessageShape::Mentions) {
+            let mentions = [
+                ("user", msg.mentions.len()),
+                ("role", msg.mention_roles.len()),
+                ("everyone", usize::from(msg.mention_everyone)),
+            ];
+            for (mention_type, count) in mentions {
+                self.metrics_handler
+                    .message_mentions
+                    .get_or_create(&MessageMentionsLabels::new(
+                        category_id,
+                        channel_id,
+                        mention_type,
+                    ))
+                    .inc_by(count.try_into().expect("expected to fit in u64"));
+            }
+        }
+
+        // Handle `message_reply` metric
+        if shapes.contains(&MessageShape::Reply) && msg.kind == MessageType::InlineReply {
+            self.metrics_handler
+                .message_reply
+                .get_or_create(&labels)
+                .inc();
+        }
+    }
+
+    /// Decrements the `reaction_active` metric for reactions that are no longer tracked. Zero counts are skipped, such
+    /// that no empty series are created.
+    fn deactivate_reactions(&self, reactions: HashMap<ReactionEmoji, u64>) {
+        for (emoji, count) in reactions.into_iter().filter(|(_, count)| *count > 0) {
+            self.metrics_handler
+                .reaction_active
+                .get_or_create(&ReactionActiveLabels::new(&emoji))
+                .dec_by(count.try_into().expect("expected to fit in i64"));
+        }
+    }
+}
+
+/// Gets the guild of a guild channel. It expects the guild to be cached.
+fn channel_guild(ctx: &Context, channel_id: ChannelId) -> Option<GuildId> {
+    ctx.cache.guilds().into_iter().find(|guild_id| {
+        ctx.cache
+            .guild(*guild_id)
+            .is_some_and(|guild| guild.channels.contains_key(&channel_id))
+    })
+}
+
+/// Checks whether an audit log entry sets a new (or extends an) active timeout of a member.
+fn is_timeout(entry: &AuditLogEntry) -> bool {
+    entry.changes.iter().flatten().any(|change| {
+        matches!(
+            change,
+            Change::CommunicationDisabledUntil { old, new: Some(new) }
+                if old.as_ref() != Some(new) && active_timeout(Some(*new)).is_some()
+        )
+    })
+}
+
+/// Gets the timeout expiry of a member if the timeout is still active.
+fn active_timeout(until: Option<Timestamp>) -> Option<Timestamp> {
+    let now = Timestamp::now().unix_timestamp();
+    until.filter(|until| until.unix_timestamp() > now)
+}
+
+/// Gets the highest role of a guild member. It expects the guild and member to be cached.
+fn top_role(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<RoleId> {
+    let guild = ctx.cache.guild(guild_id)?;
+    let member = guild.members.get(&user_id)?;
+    guild.member_highest_role(member).map(|role| role.id)
+}
+
+/// Checks whether a channel is a voice or stage channel.
+fn is_voice(channel: &GuildChannel) -> bool {
+    matches!(channel.kind, ChannelType::Voice | ChannelType::Stage)
+}
+
+/// Checks whether a channel is a stage channel. It expects the guild to be cached.
+fn is_stage(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> bool {
+    ctx.cache
+        .guild(guild_id)
+        .and_then(|guild| guild.channels.get(&channel_id).map(|channel| channel.kind))
+        .is_some_and(|kind| kind == ChannelType::Stage)
+}
+
+/// Gets the labels of the `member_client_status` metric for all platforms of a presence.
+fn client_statuses(presence: &Presence) -> Vec<MemberClientStatusLabels> {
+    let Some(client_status) = &presence.client_status else {
+        return Vec::new();
+    };
+    [
+        ("desktop", client_status.desktop),
+        ("mobile", client_status.mobile),
+        ("web", client_status.web),
+    ]
+    .into_iter()
+    .filter_map(|(platform, status)| {
+        status.map(|status| MemberClientStatusLabels::new(platform, status))
+    })
+    .collect()
+}
+
+/// Checks whether a status is visible as online (including idle and do not disturb).
+fn is_online(status: OnlineStatus) -> bool {
+    !matches!(status, OnlineStatus::Offline | OnlineStatus::Invisible)
+}
+
+/// Checks whether a user is a bot or system user. Unknown users are considered to be regular users.
+fn is_bot(ctx: &Context, user_id: Option<UserId>) -> bool {
+    user_id
+        .and_then(|user_id| ctx.cache.user(user_id))
+        .is_some_and(|user| user.bot || user.system)
 }
 
 /// Gets the root category and channel for a guild channel. It expects all relevant items to be cached.
-fn category_channel(
+pub fn category_channel(
     ctx: &Context,
     guild_id: GuildId,
     channel_id: ChannelId,
@@ -70,6 +802,38 @@ fn category_channel(
     (Some(category.id), channel.id)
 }
 
+/// Gets the root category and channel for a guild channel or thread without panicking. Returns [None] if any of the
+/// relevant items is not cached.
+pub fn find_category_channel(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Option<(Option<ChannelId>, ChannelId)> {
+    let guild = ctx.cache.guild(guild_id)?;
+    let find = |channel_id: ChannelId| {
+        guild
+            .channels
+            .get(&channel_id)
+            .or_else(|| guild.threads.iter().find(|thread| thread.id == channel_id))
+    };
+    let mut channel = find(channel_id)?;
+
+    // Handle category
+    let Some(parent_id) = channel.parent_id else {
+        return Some((None, channel.id));
+    };
+    let category = find(parent_id)?;
+
+    // Handle thread
+    let Some(parent_id) = category.parent_id else {
+        return Some((Some(category.id), channel.id));
+    };
+    channel = category;
+    let category = find(parent_id)?;
+
+    Some((Some(category.id), channel.id))
+}
+
 #[async_trait]
 impl EventHandler for Handler {
     async fn channel_create(&self, _ctx: Context, channel: GuildChannel) {
@@ -83,6 +847,14 @@ impl EventHandler for Handler {
             .channel
             .get_or_create(&ChannelLabels::new(&channel))
             .set(1);
+
+        // Handle `voice_channel_capacity` metric
+        if is_voice(&channel) {
+            self.metrics_handler
+                .voice_channel_capacity
+                .get_or_create(&VoiceChannelCapacityLabels::new(&channel))
+                .set(channel.user_limit.unwrap_or(0).into());
+        }
     }
 
     async fn channel_delete(
@@ -100,6 +872,13 @@ impl EventHandler for Handler {
         self.metrics_handler
             .channel
             .remove(&ChannelLabels::new(&channel));
+
+        // Handle `voice_channel_capacity` metric
+        if is_voice(&channel) {
+            self.metrics_handler
+                .voice_channel_capacity
+                .remove(&VoiceChannelCapacityLabels::new(&channel));
+        }
     }
 
     async fn channel_update(&self, _ctx: Context, old: Option<GuildChannel>, new: GuildChannel) {
@@ -114,6 +893,9 @@ impl EventHandler for Handler {
             self.metrics_handler
                 .channel
                 .remove(&ChannelLabels::new(&old));
+            self.metrics_handler
+                .voice_channel_capacity
+                .remove(&VoiceChannelCapacityLabels::new(&old));
         }
 
         // Increment new
@@ -121,6 +903,14 @@ impl EventHandler for Handler {
             .channel
             .get_or_create(&ChannelLabels::new(&new))
             .set(1);
+
+        // Handle `voice_channel_capacity` metric
+        if is_voice(&new) {
+            self.metrics_handler
+                .voice_channel_capacity
+                .get_or_create(&VoiceChannelCapacityLabels::new(&new))
+                .set(new.user_limit.unwrap_or(0).into());
+        }
     }
 
     async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: Option<bool>) {
@@ -132,6 +922,14 @@ impl EventHandler for Handler {
             error!("guild already created");
             self.metrics_handler.clear();
             self.users.write().await.clear();
+            self.reactions.write().await.clear();
+            self.raid.write().await.clear();
+            self.timeouts.write().await.clear();
+            self.automod_rules.write().await.clear();
+            self.member_roles.write().await.clear();
+            self.activity_sessions.write().await.clear(now_millis());
+            self.online_peaks.write().await.clear();
+            self.stage_instances.write().await.clear();
         }
         *created = true;
 
@@ -149,6 +947,44 @@ impl EventHandler for Handler {
                 .set(1);
         }
 
+        // Handle `voice_channel_capacity` metric
+        for channel in guild.channels.values().filter(|channel| is_voice(channel)) {
+            self.metrics_handler
+                .voice_channel_capacity
+                .get_or_create(&VoiceChannelCapacityLabels::new(channel))
+                .set(channel.user_limit.unwrap_or(0).into());
+        }
+
+        // Handle `role` metric
+        for role in guild.roles.values() {
+            self.metrics_handler
+                .role
+                .get_or_create(&RoleLabels::new(role))
+                .set(1);
+        }
+
+        // Handle `stage_instance` metric
+        for stage_instance in &guild.stage_instances {
+            self.update_stage_instance(&ctx, stage_instance).await;
+        }
+
+        // Handle `automod_rule` metric
+        match guild.id.automod_rules(&ctx.http).await {
+            Ok(rules) => {
+                let mut automod_rules = self.automod_rules.write().await;
+                for rule in rules {
+                    self.metrics_handler
+                        .automod_rule
+                        .get_or_create(&AutomodRuleLabels::new(&rule))
+                        .set(1);
+                    automod_rules.insert(rule.id, rule);
+                }
+            }
+            Err(why) => {
+                warn!(guild_id = guild.id.get(), err = %why, "Failed to fetch AutoMod rules");
+            }
+        }
+
         // Handle `boost` metric
         self.metrics_handler
             .boost
@@ -174,6 +1010,8 @@ impl EventHandler for Handler {
 
         // Handle `bot` metric
         let mut members_after = None;
+        let mut member_ids = HashSet::new();
+        let mut complete = false;
         loop {
             let Ok(members) = guild.members(&ctx.http, None, members_after).await else {
                 warn!(guild_id = guild.id.get(), "Failed to count guild bots");
@@ -192,29 +1030,96 @@ impl EventHandler for Handler {
                         .try_into()
                         .expect("expected to fit in i64"),
                 );
+
+            // Handle `role_member` metric (members may already be cached by concurrent member events). Tier role
+            // changes are not applied, as `member_status` and `member_voice` are only seeded below (with the current
+            // tier role) and would otherwise be decremented for labels that were never incremented.
+            let mut member_roles = self.member_roles.write().await;
+            for member in &members {
+                let old = member_roles.insert(member.user.id, member.roles.clone());
+                self.update_member_roles(old.as_deref().unwrap_or_default(), &member.roles);
+            }
+            drop(member_roles);
+
+            // Handle `member_timed_out` metric
+            self.timeouts
+                .write()
+                .await
+                .extend(members.iter().filter_map(|member| {
+                    active_timeout(member.communication_disabled_until)
+                        .map(|until| (member.user.id, until))
+                }));
+
+            // Handle `member_inactive` metric (members that were never seen are treated as active now)
+            if let Some(last_seen) = &self.last_seen {
+                let user_ids = members
+                    .iter()
+                    .filter(|member| !member.user.bot && !member.user.system)
+                    .map(|member| member.user.id);
+                member_ids.extend(user_ids.clone());
+                last_seen.observe(user_ids, now_millis() / 1000).await;
+            }
+
             let Some(last) = members.last() else {
+                complete = true;
                 break;
             };
             members_after = Some(last.user.id);
         }
 
+        // Handle `member_inactive` metric (remove members that left while offline, if all members were fetched)
+        if let Some(last_seen) = &self.last_seen {
+            if complete {
+                last_seen
+                    .retain(|user_id| member_ids.contains(user_id))
+                    .await;
+            }
+        }
+
+        let timeouts = self.timeouts.read().await.len();
+        self.update_timed_out(timeouts);
+
         for (user_id, presence) in &guild.presences {
             debug!(user_id = user_id.get(), "create presence");
+            let tier_role_id = self.member_tier_role(*user_id).await;
 
             // Handle `member_status` metric
             self.metrics_handler
                 .member_status
-                .get_or_create(&MemberStatusLabels::new(presence.status))
+                .get_or_create(&MemberStatusLabels::new(presence.status, tier_role_id))
                 .inc();
 
+            // Handle `member_online_peak` metric
+            self.update_online_peaks(*user_id, is_online(presence.status))
+                .await;
+
+            // Handle `member_inactive` metric
+            if is_online(presence.status) {
+                self.touch_last_seen(&ctx, *user_id).await;
+            }
+
+            // Handle `member_client_status` metric
+            for labels in client_statuses(presence) {
+                self.metrics_handler
+                    .member_client_status
+                    .get_or_create(&labels)
+                    .inc();
+            }
+
             // Handle `activity` metric
-            for activity in &presence.activities {
+            for activity in self.activities(presence) {
                 self.metrics_handler
                     .activity
-                    .get_or_create(&ActivityLabels::new(activity))
+                    .get_or_create(&ActivityLabels::new(
+                        activity,
+                        &self.config.activity_aliases,
+                    ))
                     .inc();
             }
 
+            // Handle `activity_seconds` metric
+            self.update_activity_sessions(presence).await;
+
             // store user presences into handler cache such that the metrics can be decremented on the next presence update
             self.users.write().await.insert(
                 *user_id,
@@ -224,13 +1129,43 @@ impl EventHandler for Handler {
             );
         }
 
+        // Handle `voice_seconds` and `voice_session_duration_seconds` metric (continue sessions of reconnects)
+        let now = Instant::now();
+        let sessions: Vec<UserId> = self.voice_sessions.read().await.keys().copied().collect();
+        for user_id in sessions {
+            let channel = guild
+                .voice_states
+                .get(&user_id)
+                .and_then(|voice| voice.channel_id)
+                .map(|channel_id| {
+                    find_category_channel(&ctx, guild.id, channel_id).unwrap_or((None, channel_id))
+                });
+            self.update_voice_session(user_id, channel, now).await;
+        }
+        for voice in guild.voice_states.values() {
+            let channel = voice.channel_id.map(|channel_id| {
+                find_category_channel(&ctx, guild.id, channel_id).unwrap_or((None, channel_id))
+            });
+            self.update_voice_session(voice.user_id, channel, now).await;
+        }
+
         // Handle `member_voice` metric
         for voice in guild.voice_states.values() {
             if let Some(channel_id) = &voice.channel_id {
+                if self.is_afk_excluded(&ctx, guild.id, *channel_id) {
+                    continue;
+                }
                 let (category_id, channel_id) = category_channel(&ctx, guild.id, *channel_id);
+                let tier_role_id = self.member_tier_role(voice.user_id).await;
                 self.metrics_handler
                     .member_voice
-                    .get_or_create(&MemberVoiceLabels::new(category_id, channel_id, voice))
+                    .get_or_create(&MemberVoiceLabels::new(
+                        category_id,
+                        channel_id,
+                        voice,
+                        is_stage(&ctx, guild.id, channel_id),
+                        tier_role_id,
+                    ))
                     .inc();
             }
         }
@@ -244,79 +1179,476 @@ impl EventHandler for Handler {
     ) {
         info!(guild_id = incomplete.id.get(), "Guild delete");
 
-        // clear all metrics to prevent inconsistencies (only supports a single guild)
+        // clear all metrics to prevent inconsistencies (only supports a single guild). Voice sessions are kept while
+        // the guild is unavailable, such that their seconds are flushed into `voice_seconds` once it is available again.
         let mut created = self.created.write().await;
         if !*created {
             error!("guild not created");
         }
         self.metrics_handler.clear();
         self.users.write().await.clear();
+        self.reactions.write().await.clear();
+        self.raid.write().await.clear();
+        self.timeouts.write().await.clear();
+        self.automod_rules.write().await.clear();
+        self.member_roles.write().await.clear();
+        if !incomplete.unavailable {
+            self.voice_sessions.write().await.clear();
+        }
+        self.activity_sessions.write().await.clear(now_millis());
+        self.online_peaks.write().await.clear();
+        self.stage_instances.write().await.clear();
         *created = false;
     }
 
     async fn guild_member_addition(&self, _ctx: Context, new_member: Member) {
         info!(
-            guild_id = new_member.guild_id.get(),
-            user_id = new_member.user.id.get(),
-            "Guild member addition"
+            guild_id = new_member.guild_id.get(),
+            user_id = new_member.user.id.get(),
+            "Guild member addition"
+        );
+
+        // Handle `member` metric
+        self.metrics_handler
+            .member
+            .get_or_create(&MemberLabels::new())
+            .inc();
+
+        // Handle `role_member` metric (the member may already be seeded by the guild create)
+        let old = self
+            .member_roles
+            .write()
+            .await
+            .insert(new_member.user.id, new_member.roles.clone());
+        self.update_member_roles(old.as_deref().unwrap_or_default(), &new_member.roles);
+
+        // Handle `member_joined` metric
+        self.metrics_handler
+            .member_joined
+            .get_or_create(&MemberJoinedLabels::new())
+            .inc();
+
+        // Handle `member_joined_young` metric
+        let account_age =
+            Timestamp::now().unix_timestamp() - new_member.user.id.created_at().unix_timestamp();
+        let young_account_seconds = self.config.young_account_days.saturating_mul(86_400);
+        if u64::try_from(account_age).is_ok_and(|age| age < young_account_seconds) {
+            self.metrics_handler
+                .member_joined_young
+                .get_or_create(&MemberJoinedYoungLabels::new())
+                .inc();
+        }
+
+        // Handle `raid_suspected` metric
+        let state = self.raid.write().await.record(Instant::now());
+        self.update_raid(state).await;
+
+        // Handle `member_inactive` metric (new members are treated as active now)
+        if let Some(last_seen) = &self.last_seen {
+            if !new_member.user.bot && !new_member.user.system {
+                last_seen
+                    .touch(new_member.user.id, now_millis() / 1000)
+                    .await;
+            }
+        }
+
+        // Handle `bot` metric
+        if new_member.user.bot {
+            self.metrics_handler
+                .bot
+                .get_or_create(&BotLabels::new())
+                .inc();
+        }
+    }
+
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        member_data_if_available: Option<Member>,
+    ) {
+        info!(
+            guild_id = guild_id.get(),
+            user_id = user.id.get(),
+            "Guild member removal"
+        );
+
+        // Handle `member` metric
+        self.metrics_handler
+            .member
+            .get_or_create(&MemberLabels::new())
+            .dec();
+
+        // Handle `member_inactive` metric
+        if let Some(last_seen) = &self.last_seen {
+            last_seen.remove(user.id).await;
+        }
+
+        // Handle `member_online_peak` metric
+        self.update_online_peaks(user.id, false).await;
+
+        // Handle `activity_seconds` metric
+        let seconds = self
+            .activity_sessions
+            .write()
+            .await
+            .end(user.id, now_millis());
+        self.add_activity_seconds(seconds);
+
+        // Handle `member_left` metric
+        self.metrics_handler
+            .member_left
+            .get_or_create(&MemberLeftLabels::new())
+            .inc();
+
+        // Handle `member_tenure` metric
+        if let Some(joined_at) = member_data_if_available.and_then(|member| member.joined_at) {
+            let tenure = Timestamp::now().unix_timestamp() - joined_at.unix_timestamp();
+            #[allow(clippy::cast_precision_loss)]
+            self.metrics_handler
+                .member_tenure
+                .get_or_create(&MemberTenureLabels::new())
+                .observe(tenure.max(0) as f64);
+        }
+
+        // Handle `role_member` metric
+        let roles = self.member_roles.write().await.remove(&user.id);
+        if let Some(roles) = roles {
+            self.update_member_roles(&roles, &[]);
+
+            // Handle tier role change
+            if let Some(tier_role_id) = self.tier_role(&roles) {
+                self.update_tier_role(&ctx, guild_id, user.id, Some(tier_role_id), None)
+                    .await;
+            }
+        }
+
+        // Handle `member_timed_out` metric
+        let mut timeouts = self.timeouts.write().await;
+        if timeouts.remove(&user.id).is_some() {
+            self.update_timed_out(timeouts.len());
+        }
+        drop(timeouts);
+
+        // Handle `bot` metric
+        if user.bot {
+            self.metrics_handler
+                .bot
+                .get_or_create(&BotLabels::new())
+                .dec();
+        }
+    }
+
+    async fn guild_member_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Member>,
+        _new: Option<Member>,
+        event: GuildMemberUpdateEvent,
+    ) {
+        info!(
+            guild_id = event.guild_id.get(),
+            user_id = event.user.id.get(),
+            "Guild member update"
+        );
+
+        // Handle `role_member` metric (uncached members are skipped, they are counted once the guild is seeded)
+        let old = self
+            .member_roles
+            .write()
+            .await
+            .get_mut(&event.user.id)
+            .map(|roles| std::mem::replace(roles, event.roles.clone()));
+        if let Some(old) = old {
+            self.update_member_roles(&old, &event.roles);
+
+            // Handle tier role change
+            let old_tier_role_id = self.tier_role(&old);
+            let new_tier_role_id = self.tier_role(&event.roles);
+            if old_tier_role_id != new_tier_role_id {
+                self.update_tier_role(
+                    &ctx,
+                    event.guild_id,
+                    event.user.id,
+                    old_tier_role_id,
+                    new_tier_role_id,
+                )
+                .await;
+            }
+        } else {
+            debug!(
+                user_id = event.user.id.get(),
+                "Skipped role update of uncached member"
+            );
+        }
+
+        // Handle `member_timeout` and `member_timed_out` metric (new timeouts and extensions are counted)
+        let mut timeouts = self.timeouts.write().await;
+        let changed = match active_timeout(event.communication_disabled_until) {
+            Some(until) => {
+                let previous = timeouts.insert(event.user.id, until);
+                if active_timeout(previous) != Some(until) {
+                    self.metrics_handler
+                        .member_timeout
+                        .get_or_create(&MemberTimeoutLabels::new())
+                        .inc();
+                }
+                previous.is_none()
+            }
+            None => timeouts.remove(&event.user.id).is_some(),
+        };
+        if changed {
+            self.update_timed_out(timeouts.len());
+        }
+    }
+
+    async fn guild_role_create(&self, _ctx: Context, new: Role) {
+        info!(
+            guild_id = new.guild_id.get(),
+            role_id = new.id.get(),
+            "Guild role create"
+        );
+
+        // Handle `role` metric
+        self.metrics_handler
+            .role
+            .get_or_create(&RoleLabels::new(&new))
+            .set(1);
+    }
+
+    async fn guild_role_update(
+        &self,
+        _ctx: Context,
+        old_data_if_available: Option<Role>,
+        new: Role,
+    ) {
+        info!(
+            guild_id = new.guild_id.get(),
+            role_id = new.id.get(),
+            "Guild role update"
+        );
+
+        // Handle `role` metric
+        if let Some(old) = old_data_if_available {
+            self.metrics_handler.role.remove(&RoleLabels::new(&old));
+        }
+        self.metrics_handler
+            .role
+            .get_or_create(&RoleLabels::new(&new))
+            .set(1);
+    }
+
+    async fn guild_role_delete(
+        &self,
+        _ctx: Context,
+        guild_id: GuildId,
+        removed_role_id: RoleId,
+        removed_role_data_if_available: Option<Role>,
+    ) {
+        info!(
+            guild_id = guild_id.get(),
+            role_id = removed_role_id.get(),
+            "Guild role delete"
+        );
+
+        // Handle `role` metric
+        if let Some(old) = removed_role_data_if_available {
+            self.metrics_handler.role.remove(&RoleLabels::new(&old));
+        }
+
+        // Handle `role_member` metric
+        for roles in self.member_roles.write().await.values_mut() {
+            roles.retain(|role_id| *role_id != removed_role_id);
+        }
+        self.metrics_handler
+            .role_member
+            .remove(&RoleMemberLabels::new(removed_role_id));
+    }
+
+    async fn guild_ban_addition(&self, _ctx: Context, guild_id: GuildId, banned_user: User) {
+        info!(
+            guild_id = guild_id.get(),
+            user_id = banned_user.id.get(),
+            "Guild ban addition"
+        );
+
+        // Handle `member_banned` metric
+        self.metrics_handler
+            .member_banned
+            .get_or_create(&MemberBannedLabels::new())
+            .inc();
+    }
+
+    async fn guild_ban_removal(&self, _ctx: Context, guild_id: GuildId, unbanned_user: User) {
+        info!(
+            guild_id = guild_id.get(),
+            user_id = unbanned_user.id.get(),
+            "Guild ban removal"
+        );
+
+        // Handle `member_unbanned` metric
+        self.metrics_handler
+            .member_unbanned
+            .get_or_create(&MemberUnbannedLabels::new())
+            .inc();
+    }
+
+    async fn guild_audit_log_entry_create(
+        &self,
+        ctx: Context,
+        entry: AuditLogEntry,
+        guild_id: GuildId,
+    ) {
+        info!(
+            guild_id = guild_id.get(),
+            entry_id = entry.id.get(),
+            "Guild audit log entry create"
         );
+        let user_role_id = top_role(&ctx, guild_id, entry.user_id);
 
-        // Handle `member` metric
+        // Handle `audit_log_entry` metric
         self.metrics_handler
-            .member
-            .get_or_create(&MemberLabels::new())
+            .audit_log_entry
+            .get_or_create(&AuditLogEntryLabels::new(&entry.action, user_role_id))
             .inc();
 
-        // Handle `bot` metric
-        if new_member.user.bot {
+        // Handle `member_kicked` metric (kicks have no gateway event of their own)
+        if matches!(entry.action, Action::Member(MemberAction::Kick)) {
             self.metrics_handler
-                .bot
-                .get_or_create(&BotLabels::new())
+                .member_kicked
+                .get_or_create(&MemberKickedLabels::new(user_role_id))
                 .inc();
         }
+
+        // Handle `moderator_action` metric (bans, unbans and timeouts are counted through their gateway events)
+        let action = match entry.action {
+            Action::Member(MemberAction::BanAdd) => "ban",
+            Action::Member(MemberAction::BanRemove) => "unban",
+            Action::Member(MemberAction::Kick) => "kick",
+            Action::Member(MemberAction::Update) if is_timeout(&entry) => "timeout",
+            _ => return,
+        };
+        self.metrics_handler
+            .moderator_action
+            .get_or_create(&ModeratorActionLabels::new(action, user_role_id))
+            .inc();
     }
 
-    async fn guild_member_removal(
-        &self,
-        _ctx: Context,
-        guild_id: GuildId,
-        user: User,
-        _member_data_if_available: Option<Member>,
-    ) {
+    async fn auto_moderation_rule_create(&self, _ctx: Context, rule: Rule) {
         info!(
-            guild_id = guild_id.get(),
-            user_id = user.id.get(),
-            "Guild member removal"
+            guild_id = rule.guild_id.get(),
+            rule_id = rule.id.get(),
+            "AutoMod rule create"
         );
 
-        // Handle `member` metric
+        // Handle `automod_rule` metric
         self.metrics_handler
-            .member
-            .get_or_create(&MemberLabels::new())
-            .dec();
+            .automod_rule
+            .get_or_create(&AutomodRuleLabels::new(&rule))
+            .set(1);
+        self.automod_rules.write().await.insert(rule.id, rule);
+    }
 
-        // Handle `bot` metric
-        if user.bot {
+    async fn auto_moderation_rule_update(&self, _ctx: Context, rule: Rule) {
+        info!(
+            guild_id = rule.guild_id.get(),
+            rule_id = rule.id.get(),
+            "AutoMod rule update"
+        );
+
+        // Handle `automod_rule` metric
+        let mut automod_rules = self.automod_rules.write().await;
+        if let Some(old) = automod_rules.insert(rule.id, rule.clone()) {
             self.metrics_handler
-                .bot
-                .get_or_create(&BotLabels::new())
-                .dec();
+                .automod_rule
+                .remove(&AutomodRuleLabels::new(&old));
+            self.metrics_handler
+                .automod_rule
+                .get_or_create(&AutomodRuleLabels::new(&rule))
+                .set(1);
+        } else {
+            // The previous labels are unknown, so the series are rebuilt from the known rules
+            self.metrics_handler.automod_rule.clear();
+            for rule in automod_rules.values() {
+                self.metrics_handler
+                    .automod_rule
+                    .get_or_create(&AutomodRuleLabels::new(rule))
+                    .set(1);
+            }
         }
     }
 
+    async fn auto_moderation_rule_delete(&self, _ctx: Context, rule: Rule) {
+        info!(
+            guild_id = rule.guild_id.get(),
+            rule_id = rule.id.get(),
+            "AutoMod rule delete"
+        );
+
+        // Handle `automod_rule` metric
+        let old = self.automod_rules.write().await.remove(&rule.id);
+        self.metrics_handler
+            .automod_rule
+            .remove(&AutomodRuleLabels::new(old.as_ref().unwrap_or(&rule)));
+    }
+
+    async fn auto_moderation_action_execution(&self, ctx: Context, execution: ActionExecution) {
+        info!(
+            guild_id = execution.guild_id.get(),
+            rule_id = execution.rule_id.get(),
+            "AutoMod action execution"
+        );
+
+        // Handle `automod_action` metric
+        let rule_name = self
+            .automod_rules
+            .read()
+            .await
+            .get(&execution.rule_id)
+            .map(|rule| rule.name.clone());
+        // AutoMod often fires in threads or uncached channels, the raw channel is used as a fallback
+        let (category_id, channel_id) = match execution.channel_id {
+            Some(channel_id) => {
+                let (category_id, channel_id) =
+                    find_category_channel(&ctx, execution.guild_id, channel_id)
+                        .unwrap_or((None, channel_id));
+                (category_id, Some(channel_id))
+            }
+            None => (None, None),
+        };
+        self.metrics_handler
+            .automod_action
+            .get_or_create(&AutomodActionLabels::new(
+                &execution,
+                rule_name,
+                category_id,
+                channel_id,
+            ))
+            .inc();
+    }
+
     async fn guild_update(
         &self,
-        _ctx: Context,
+        ctx: Context,
         old_data_if_available: Option<Guild>,
         new_data: PartialGuild,
     ) {
         info!(guild_id = new_data.id.get(), "Guild Update");
 
         // Handle `guild` metric
-        if let Some(guild) = old_data_if_available {
-            self.metrics_handler
-                .guild
-                .remove(&GuildsLabels::new(&guild));
+        if let Some(guild) = &old_data_if_available {
+            self.metrics_handler.guild.remove(&GuildsLabels::new(guild));
+        }
+
+        // Handle `member_voice` metric (members in the old or new AFK channel are no longer or now excluded)
+        if let Some(guild) = &old_data_if_available {
+            let old_afk_channel_id = guild.afk_metadata.as_ref().map(|afk| afk.afk_channel_id);
+            let new_afk_channel_id = new_data.afk_metadata.as_ref().map(|afk| afk.afk_channel_id);
+            if self.config.exclude_afk && old_afk_channel_id != new_afk_channel_id {
+                self.update_afk_voice(&ctx, new_data.id, old_afk_channel_id, new_afk_channel_id)
+                    .await;
+            }
         }
 
         // Handle `boost` metric
@@ -339,39 +1671,201 @@ impl EventHandler for Handler {
         };
         info!(guild_id = guild_id.get(), "Message");
 
-        if msg.author.bot || msg.author.system {
-            // Only tracks user messages
+        if msg.author.bot || msg.webhook_id.is_some() {
+            // Handle `message_kind` metric (interaction replies are authored by bots on behalf of members)
+            if matches!(
+                msg.kind,
+                MessageType::ChatInputCommand | MessageType::ContextMenuCommand
+            ) {
+                let (category_id, channel_id) = category_channel(&ctx, guild_id, msg.channel_id);
+                self.metrics_handler
+                    .message_kind
+                    .get_or_create(&MessageKindLabels::new(category_id, channel_id, msg.kind))
+                    .inc();
+            }
+
+            // Handle `bot_message_sent` metric
+            let allowed = [
+                Some(msg.author.id.get()),
+                msg.webhook_id.map(WebhookId::get),
+                msg.application_id.map(ApplicationId::get),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|id| self.config.bot_allowlist.contains(&id));
+            if allowed {
+                let (category_id, channel_id) = category_channel(&ctx, guild_id, msg.channel_id);
+                self.metrics_handler
+                    .bot_message_sent
+                    .get_or_create(&BotMessageSentLabels::new(category_id, channel_id, &msg))
+                    .inc();
+            }
+            return;
+        }
+
+        if msg.author.system && !self.config.system_messages {
+            // Only tracks user (and optionally system) messages
             return;
         }
 
         let (category_id, channel_id) = category_channel(&ctx, guild_id, msg.channel_id);
+        let tier_role_id = self.member_tier_role(msg.author.id).await;
+
+        // Handle `active_users` and `member_inactive` metric
+        self.record_active_user(msg.author.id).await;
+        self.touch_last_seen(&ctx, msg.author.id).await;
 
         // Handle `message_sent` metric
         self.metrics_handler
             .message_sent
-            .get_or_create(&MessageSentLabels::new(category_id, channel_id))
+            .get_or_create(&MessageSentLabels::new(
+                category_id,
+                channel_id,
+                tier_role_id,
+            ))
             .inc();
 
-        // Handle `emote_used` metric
+        // Handle `message_kind` metric
+        self.metrics_handler
+            .message_kind
+            .get_or_create(&MessageKindLabels::new(category_id, channel_id, msg.kind))
+            .inc();
+
+        // Handle message shape metrics
+        self.observe_message_shape(&msg, category_id, channel_id);
+
+        // Handle `message_matched` metric
+        if let Some(patterns) = &self.patterns {
+            for pattern in patterns.matches(&msg.content).await {
+                self.metrics_handler
+                    .message_matched
+                    .get_or_create(&MessageMatchedLabels::new(pattern, category_id, channel_id))
+                    .inc();
+            }
+        }
+
+        // Handle `emote_used` metric (custom emojis)
         for part in msg.content.split_whitespace() {
             let Some(emoji) = parse_emoji(part) else {
-                // Only tracks custom emojis
                 continue;
             };
 
+            let emoji = ReactionEmoji::Custom {
+                id: emoji.id,
+                name: Some(emoji.name),
+            };
+            self.metrics_handler
+                .emote_used
+                .get_or_create(&EmoteUsedLabels::new(
+                    category_id,
+                    channel_id,
+                    false,
+                    &emoji,
+                ))
+                .inc();
+        }
+
+        // Handle `emote_used` metric (unicode emojis)
+        for name in msg.content.graphemes(true).filter_map(unicode_emoji_name) {
+            let emoji = ReactionEmoji::Unicode { name };
             self.metrics_handler
                 .emote_used
                 .get_or_create(&EmoteUsedLabels::new(
                     category_id,
                     channel_id,
                     false,
-                    emoji.id,
-                    Some(emoji.name),
+                    &emoji,
                 ))
                 .inc();
         }
     }
 
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        let Some(guild_id) = event.guild_id else {
+            // Only tracks guild events
+            return;
+        };
+        info!(guild_id = guild_id.get(), "Message update");
+
+        if event.edited_timestamp.is_none() {
+            // Only tracks content edits (not embed updates)
+            return;
+        }
+
+        if let Some(author) = &event.author {
+            if author.bot || author.system {
+                // Only tracks user messages
+                return;
+            }
+        }
+
+        let (category_id, channel_id) = find_category_channel(&ctx, guild_id, event.channel_id)
+            .unwrap_or((None, event.channel_id));
+
+        // Handle `message_edited` metric
+        self.metrics_handler
+            .message_edited
+            .get_or_create(&MessageEditedLabels::new(category_id, channel_id))
+            .inc();
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        _deleted_message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        let Some(guild_id) = guild_id else {
+            // Only tracks guild events
+            return;
+        };
+        info!(guild_id = guild_id.get(), "Message delete");
+
+        let (category_id, channel_id) =
+            find_category_channel(&ctx, guild_id, channel_id).unwrap_or((None, channel_id));
+
+        // Handle `message_deleted` metric
+        self.metrics_handler
+            .message_deleted
+            .get_or_create(&MessageDeletedLabels::new(category_id, channel_id, false))
+            .inc();
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        let Some(guild_id) = guild_id else {
+            // Only tracks guild events
+            return;
+        };
+        info!(guild_id = guild_id.get(), "Message delete bulk");
+
+        let (category_id, channel_id) =
+            find_category_channel(&ctx, guild_id, channel_id).unwrap_or((None, channel_id));
+
+        // Handle `message_deleted` metric
+        self.metrics_handler
+            .message_deleted
+            .get_or_create(&MessageDeletedLabels::new(category_id, channel_id, true))
+            .inc_by(
+                multiple_deleted_messages_ids
+                    .len()
+                    .try_into()
+                    .expect("expected to fit in u64"),
+            );
+    }
+
     async fn reaction_add(&self, ctx: Context, add_reaction: Reaction) {
         let Some(guild_id) = add_reaction.guild_id else {
             // Only tracks guild events
@@ -386,8 +1880,17 @@ impl EventHandler for Handler {
             }
         }
 
-        let ReactionType::Custom { name, id, .. } = add_reaction.emoji else {
-            // Only tracks custom emojis
+        // Handle `active_users` and `member_inactive` metric
+        if let Some(user_id) = add_reaction
+            .user_id
+            .filter(|user_id| !is_bot(&ctx, Some(*user_id)))
+        {
+            self.record_active_user(user_id).await;
+            self.touch_last_seen(&ctx, user_id).await;
+        }
+
+        let Some(emoji) = ReactionEmoji::new(&add_reaction.emoji) else {
+            // Only tracks custom and known unicode emojis
             return;
         };
 
@@ -396,17 +1899,123 @@ impl EventHandler for Handler {
         // Handle `emote_used` metric
         self.metrics_handler
             .emote_used
-            .get_or_create(&EmoteUsedLabels::new(
-                category_id,
-                channel_id,
-                true,
-                id,
-                name,
-            ))
+            .get_or_create(&EmoteUsedLabels::new(category_id, channel_id, true, &emoji))
+            .inc();
+
+        // Handle `reaction_active` metric
+        let mut reactions = self.reactions.write().await;
+        if reactions.is_enabled() {
+            if let Some(evicted) = reactions.add(add_reaction.message_id, emoji.clone()) {
+                self.deactivate_reactions(evicted);
+            }
+            self.metrics_handler
+                .reaction_active
+                .get_or_create(&ReactionActiveLabels::new(&emoji))
+                .inc();
+        }
+    }
+
+    async fn reaction_remove(&self, ctx: Context, removed_reaction: Reaction) {
+        let Some(guild_id) = removed_reaction.guild_id else {
+            // Only tracks guild events
+            return;
+        };
+        info!(guild_id = guild_id.get(), "Reaction remove");
+
+        if is_bot(&ctx, removed_reaction.user_id) {
+            // Only tracks user reactions
+            return;
+        }
+
+        let Some(emoji) = ReactionEmoji::new(&removed_reaction.emoji) else {
+            // Only tracks custom and known unicode emojis
+            return;
+        };
+
+        let (category_id, channel_id) =
+            find_category_channel(&ctx, guild_id, removed_reaction.channel_id)
+                .unwrap_or((None, removed_reaction.channel_id));
+
+        // Handle `emote_removed` metric
+        self.metrics_handler
+            .emote_removed
+            .get_or_create(&EmoteRemovedLabels::new(category_id, channel_id, &emoji))
+            .inc();
+
+        // Handle `reaction_active` metric (decrement)
+        if self
+            .reactions
+            .write()
+            .await
+            .remove(removed_reaction.message_id, &emoji)
+        {
+            self.metrics_handler
+                .reaction_active
+                .get_or_create(&ReactionActiveLabels::new(&emoji))
+                .dec();
+        }
+    }
+
+    async fn reaction_remove_all(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        removed_from_message_id: MessageId,
+    ) {
+        let Some(guild_id) = channel_guild(&ctx, channel_id) else {
+            // Only tracks guild events
+            return;
+        };
+        info!(guild_id = guild_id.get(), "Reaction remove all");
+
+        let (category_id, channel_id) =
+            find_category_channel(&ctx, guild_id, channel_id).unwrap_or((None, channel_id));
+
+        // Handle `reaction_cleared` metric
+        self.metrics_handler
+            .reaction_cleared
+            .get_or_create(&ReactionClearedLabels::new(category_id, channel_id, true))
+            .inc();
+
+        // Handle `reaction_active` metric (decrement)
+        let removed = self
+            .reactions
+            .write()
+            .await
+            .remove_all(removed_from_message_id);
+        self.deactivate_reactions(removed);
+    }
+
+    async fn reaction_remove_emoji(&self, ctx: Context, removed_reactions: Reaction) {
+        let Some(guild_id) = removed_reactions.guild_id else {
+            // Only tracks guild events
+            return;
+        };
+        info!(guild_id = guild_id.get(), "Reaction remove emoji");
+
+        let (category_id, channel_id) =
+            find_category_channel(&ctx, guild_id, removed_reactions.channel_id)
+                .unwrap_or((None, removed_reactions.channel_id));
+
+        // Handle `reaction_cleared` metric
+        self.metrics_handler
+            .reaction_cleared
+            .get_or_create(&ReactionClearedLabels::new(category_id, channel_id, false))
             .inc();
+
+        // Handle `reaction_active` metric (decrement)
+        let Some(emoji) = ReactionEmoji::new(&removed_reactions.emoji) else {
+            return;
+        };
+        let removed = self
+            .reactions
+            .write()
+            .await
+            .remove_emoji(removed_reactions.message_id, &emoji);
+        self.deactivate_reactions(HashMap::from([(emoji, removed)]));
     }
 
-    async fn presence_update(&self, _ctx: Context, new_data: Presence) {
+    async fn presence_update(&self, ctx: Context, new_data: Presence) {
         let Some(guild_id) = new_data.guild_id else {
             // Only tracks guild events
             return;
@@ -416,20 +2025,58 @@ impl EventHandler for Handler {
             user_id = new_data.user.id.get(),
             "Presence update"
         );
+        let tier_role_id = self.member_tier_role(new_data.user.id).await;
+        let old_status = self
+            .users
+            .read()
+            .await
+            .get(&new_data.user.id)
+            .map(|cached_user| cached_user.presence.status);
+
+        // Handle `status_transition` metric
+        if let Some(old_status) = old_status.filter(|status| *status != new_data.status) {
+            self.metrics_handler
+                .status_transition
+                .get_or_create(&StatusTransitionLabels::new(old_status, new_data.status))
+                .inc();
+        }
+
+        // Handle `member_online_peak` metric
+        self.update_online_peaks(new_data.user.id, is_online(new_data.status))
+            .await;
+
+        // Handle `member_inactive` metric
+        if is_online(new_data.status) {
+            self.touch_last_seen(&ctx, new_data.user.id).await;
+        }
 
         // Decrement gauges for previous state if cached
         if let Some(cached_user) = self.users.read().await.get(&new_data.user.id) {
             // Handle `member_status` metric (decrement)
             self.metrics_handler
                 .member_status
-                .get_or_create(&MemberStatusLabels::new(cached_user.presence.status))
+                .get_or_create(&MemberStatusLabels::new(
+                    cached_user.presence.status,
+                    tier_role_id,
+                ))
                 .dec();
 
+            // Handle `member_client_status` metric (decrement)
+            for labels in client_statuses(&cached_user.presence) {
+                self.metrics_handler
+                    .member_client_status
+                    .get_or_create(&labels)
+                    .dec();
+            }
+
             // Handle `activity` metric (decrement)
-            for activity in &cached_user.presence.activities {
+            for activity in self.activities(&cached_user.presence) {
                 self.metrics_handler
                     .activity
-                    .get_or_create(&ActivityLabels::new(activity))
+                    .get_or_create(&ActivityLabels::new(
+                        activity,
+                        &self.config.activity_aliases,
+                    ))
                     .dec();
             }
         }
@@ -437,17 +2084,31 @@ impl EventHandler for Handler {
         // Handle `member_status` metric
         self.metrics_handler
             .member_status
-            .get_or_create(&MemberStatusLabels::new(new_data.status))
+            .get_or_create(&MemberStatusLabels::new(new_data.status, tier_role_id))
             .inc();
 
+        // Handle `member_client_status` metric
+        for labels in client_statuses(&new_data) {
+            self.metrics_handler
+                .member_client_status
+                .get_or_create(&labels)
+                .inc();
+        }
+
         // Handle `activity` metric
-        for activity in &new_data.activities {
+        for activity in self.activities(&new_data) {
             self.metrics_handler
                 .activity
-                .get_or_create(&ActivityLabels::new(activity))
+                .get_or_create(&ActivityLabels::new(
+                    activity,
+                    &self.config.activity_aliases,
+                ))
                 .inc();
         }
 
+        // Handle `activity_seconds` metric
+        self.update_activity_sessions(&new_data).await;
+
         // Update cached state
         self.users
             .write()
@@ -455,6 +2116,46 @@ impl EventHandler for Handler {
             .insert(new_data.user.id, CachedUser { presence: new_data });
     }
 
+    async fn stage_instance_create(&self, ctx: Context, stage_instance: StageInstance) {
+        info!(
+            guild_id = stage_instance.guild_id.get(),
+            channel_id = stage_instance.channel_id.get(),
+            "Stage instance create"
+        );
+
+        // Handle `stage_instance` metric
+        self.update_stage_instance(&ctx, &stage_instance).await;
+    }
+
+    async fn stage_instance_update(&self, ctx: Context, stage_instance: StageInstance) {
+        info!(
+            guild_id = stage_instance.guild_id.get(),
+            channel_id = stage_instance.channel_id.get(),
+            "Stage instance update"
+        );
+
+        // Handle `stage_instance` metric
+        self.update_stage_instance(&ctx, &stage_instance).await;
+    }
+
+    async fn stage_instance_delete(&self, _ctx: Context, stage_instance: StageInstance) {
+        info!(
+            guild_id = stage_instance.guild_id.get(),
+            channel_id = stage_instance.channel_id.get(),
+            "Stage instance delete"
+        );
+
+        // Handle `stage_instance` metric (the channel may already be deleted, so the stored labels are used)
+        if let Some(labels) = self
+            .stage_instances
+            .write()
+            .await
+            .remove(&stage_instance.id)
+        {
+            self.metrics_handler.stage_instance.remove(&labels);
+        }
+    }
+
     async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
         let Some(guild_id) = new.guild_id else {
             // Only tracks guild events
@@ -465,6 +2166,7 @@ impl EventHandler for Handler {
             has_cached = old.is_some(),
             "Voice state update"
         );
+        let tier_role_id = self.member_tier_role(new.user_id).await;
 
         // Decrement gauges for previous state if cached
         'dec: {
@@ -483,12 +2185,22 @@ impl EventHandler for Handler {
                 break 'dec;
             };
 
+            if self.is_afk_excluded(&ctx, guild_id, *channel_id) {
+                break 'dec;
+            }
+
             let (category_id, channel_id) = category_channel(&ctx, guild_id, *channel_id);
 
             // Handle `member_voice` metric (decrement)
             self.metrics_handler
                 .member_voice
-                .get_or_create(&MemberVoiceLabels::new(category_id, channel_id, &old))
+                .get_or_create(&MemberVoiceLabels::new(
+                    category_id,
+                    channel_id,
+                    &old,
+                    is_stage(&ctx, guild_id, channel_id),
+                    tier_role_id,
+                ))
                 .dec();
         }
 
@@ -505,24 +2217,49 @@ impl EventHandler for Handler {
                 break 'inc;
             };
 
+            if self.is_afk_excluded(&ctx, guild_id, *channel_id) {
+                break 'inc;
+            }
+
             let (category_id, channel_id) = category_channel(&ctx, guild_id, *channel_id);
 
             // Handle `member_voice` metric
             self.metrics_handler
                 .member_voice
-                .get_or_create(&MemberVoiceLabels::new(category_id, channel_id, &new))
+                .get_or_create(&MemberVoiceLabels::new(
+                    category_id,
+                    channel_id,
+                    &new,
+                    is_stage(&ctx, guild_id, channel_id),
+                    tier_role_id,
+                ))
                 .inc();
         }
+
+        // Handle `active_users` and `member_inactive` metric
+        if new.channel_id.is_some() && !is_bot(&ctx, Some(new.user_id)) {
+            self.record_active_user(new.user_id).await;
+            self.touch_last_seen(&ctx, new.user_id).await;
+        }
+
+        // Handle `voice_seconds` and `voice_session_duration_seconds` metric
+        let channel = new.channel_id.map(|channel_id| {
+            find_category_channel(&ctx, guild_id, channel_id).unwrap_or((None, channel_id))
+        });
+        self.update_voice_session(new.user_id, channel, Instant::now())
+            .await;
     }
 }
 
 /// Serves the [`Handler`] and starts listening for guild updates.
 ///
-/// Use the [CancellationToken] to cancel and gracefully shutdown the [Handler].
-#[instrument(skip(handler, shutdown))]
+/// Use the [CancellationToken] to cancel and gracefully shutdown the [Handler]. The events are also dispatched to the
+/// [collectors](Collector).
+#[instrument(skip(handler, collectors, shutdown))]
 pub async fn serve(
     discord_token: &str,
-    handler: Handler,
+    handler: Arc<Handler>,
+    collectors: Vec<Arc<dyn Collector>>,
     shutdown: CancellationToken,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Set gateway intents, which decides what events the bot will be notified about
@@ -530,7 +2267,8 @@ pub async fn serve(
 
     // Create a new instance of the Client, logging in as a bot
     let mut client = Client::builder(discord_token, intents)
-        .event_handler(handler)
+        .event_handler_arc(handler)
+        .framework(Dispatcher::new(collectors))
         .await?;
 
     select! {
@@ -0,0 +1,243 @@
+//! This module implements the emoji normalization and the bounded cache of message reactions. The cache is used to
+//! maintain the number of currently active reactions without keeping track of every message of the guild.
+
+use emojis::SkinTone;
+use serenity::all::{EmojiId, MessageId, ReactionType};
+use std::collections::{HashMap, VecDeque};
+
+/// Gets the normalized name of a Unicode emoji (including ZWJ sequences). Skin tone variants are normalized to their
+/// default emoji.
+pub fn unicode_emoji_name(emoji: &str) -> Option<String> {
+    let emoji = emojis::get(emoji)?;
+    let emoji = emoji.with_skin_tone(SkinTone::Default).unwrap_or(emoji);
+    Some(emoji.name().to_string())
+}
+
+/// [`ReactionEmoji`] is the identity of an emoji used in reactions. Custom emojis are identified by their id, Unicode
+/// emojis only by their normalized name.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ReactionEmoji {
+    Custom { id: EmojiId, name: Option<String> },
+    Unicode { name: String },
+}
+
+impl ReactionEmoji {
+    /// Creates a new [`ReactionEmoji`] from a [`ReactionType`]. Unknown emojis are ignored.
+    pub fn new(emoji: &ReactionType) -> Option<Self> {
+        match emoji {
+            ReactionType::Custom { id, name, .. } => Some(Self::Custom {
+                id: *id,
+                name: name.clone(),
+            }),
+            ReactionType::Unicode(emoji) => Some(Self::Unicode {
+                name: unicode_emoji_name(emoji)?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Gets the id of the emoji. Only custom emojis have an id.
+    pub fn id(&self) -> Option<EmojiId> {
+        match self {
+            Self::Custom { id, .. } => Some(*id),
+            Self::Unicode { .. } => None,
+        }
+    }
+
+    /// Gets the (normalized) name of the emoji.
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Self::Custom { name, .. } => name.clone(),
+            Self::Unicode { name } => Some(name.clone()),
+        }
+    }
+}
+
+/// [`ReactionCache`] tracks the active reactions of the most recently reacted messages. If the capacity is exceeded,
+/// the least recently added message is evicted. Only reactions that were added while the message was cached are
+/// tracked.
+#[derive(Debug)]
+pub struct ReactionCache {
+    capacity: usize,
+    order: VecDeque<MessageId>,
+    messages: HashMap<MessageId, HashMap<ReactionEmoji, u64>>,
+}
+
+impl ReactionCache {
+    /// Creates a new empty [`ReactionCache`] that tracks at most `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Checks whether the cache tracks any messages at all.
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Adds a reaction to a message. Returns the reactions of the message that was evicted to make space for the new
+    /// message, if any.
+    pub fn add(
+        &mut self,
+        message_id: MessageId,
+        emoji: ReactionEmoji,
+    ) -> Option<HashMap<ReactionEmoji, u64>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut evicted = None;
+        if !self.messages.contains_key(&message_id) {
+            if self.order.len() >= self.capacity {
+                evicted = self
+                    .order
+                    .pop_front()
+                    .and_then(|evicted_id| self.messages.remove(&evicted_id));
+            }
+            self.order.push_back(message_id);
+        }
+        *self
+            .messages
+            .entry(message_id)
+            .or_default()
+            .entry(emoji)
+            .or_default() += 1;
+        evicted
+    }
+
+    /// Removes a single reaction from a message. Returns whether the reaction was tracked.
+    pub fn remove(&mut self, message_id: MessageId, emoji: &ReactionEmoji) -> bool {
+        let Some(reactions) = self.messages.get_mut(&message_id) else {
+            return false;
+        };
+        let Some(count) = reactions.get_mut(emoji) else {
+            return false;
+        };
+        *count -= 1;
+        if *count == 0 {
+            reactions.remove(emoji);
+        }
+        self.prune(message_id);
+        true
+    }
+
+    /// Removes all reactions of an emoji from a message. Returns the number of tracked reactions that were removed.
+    pub fn remove_emoji(&mut self, message_id: MessageId, emoji: &ReactionEmoji) -> u64 {
+        let removed = self
+            .messages
+            .get_mut(&message_id)
+            .and_then(|reactions| reactions.remove(emoji))
+            .unwrap_or(0);
+        self.prune(message_id);
+        removed
+    }
+
+    /// Removes all reactions from a message. Returns the tracked reactions that were removed.
+    pub fn remove_all(&mut self, message_id: MessageId) -> HashMap<ReactionEmoji, u64> {
+        let removed = self
+            .messages
+            .get_mut(&message_id)
+            .map(std::mem::take)
+            .unwrap_or_default();
+        self.prune(message_id);
+        removed
+    }
+
+    /// Removes a message from the cache if it has no tracked reactions left, such that it no longer takes up space.
+    fn prune(&mut self, message_id: MessageId) {
+        if self
+            .messages
+            .get(&message_id)
+            .is_some_and(HashMap::is_empty)
+        {
+            self.messages.remove(&message_id);
+            self.order.retain(|cached_id| *cached_id != message_id);
+        }
+    }
+
+    /// Removes all tracked messages.
+    pub fn clear(&mut self) {
+        self.order.clear();
+        self.messages.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emoji(name: &str) -> ReactionEmoji {
+        ReactionEmoji::Unicode {
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn disabled_cache_tracks_nothing() {
+        let mut cache = ReactionCache::new(0);
+        assert!(!cache.is_enabled());
+        assert_eq!(cache.add(MessageId::new(1), emoji("a")), None);
+        assert!(!cache.remove(MessageId::new(1), &emoji("a")));
+    }
+
+    #[test]
+    fn add_evicts_least_recently_added_message() {
+        let mut cache = ReactionCache::new(2);
+        assert_eq!(cache.add(MessageId::new(1), emoji("a")), None);
+        assert_eq!(cache.add(MessageId::new(1), emoji("a")), None);
+        assert_eq!(cache.add(MessageId::new(2), emoji("b")), None);
+        // Reacting to a cached message does not change its position
+        assert_eq!(cache.add(MessageId::new(1), emoji("c")), None);
+
+        let evicted = cache.add(MessageId::new(3), emoji("d"));
+        assert_eq!(
+            evicted,
+            Some(HashMap::from([(emoji("a"), 2), (emoji("c"), 1)]))
+        );
+        let evicted = cache.add(MessageId::new(4), emoji("d"));
+        assert_eq!(evicted, Some(HashMap::from([(emoji("b"), 1)])));
+    }
+
+    #[test]
+    fn remove_decrements_and_prunes_empty_messages() {
+        let mut cache = ReactionCache::new(2);
+        cache.add(MessageId::new(1), emoji("a"));
+        cache.add(MessageId::new(1), emoji("a"));
+        assert!(cache.remove(MessageId::new(1), &emoji("a")));
+        assert!(cache.remove(MessageId::new(1), &emoji("a")));
+        assert!(!cache.remove(MessageId::new(1), &emoji("a")));
+        assert!(cache.messages.is_empty());
+        assert!(cache.order.is_empty());
+    }
+
+    #[test]
+    fn remove_emoji_returns_count_and_prunes_empty_messages() {
+        let mut cache = ReactionCache::new(2);
+        cache.add(MessageId::new(1), emoji("a"));
+        cache.add(MessageId::new(1), emoji("a"));
+        cache.add(MessageId::new(1), emoji("b"));
+        assert_eq!(cache.remove_emoji(MessageId::new(1), &emoji("a")), 2);
+        assert_eq!(cache.remove_emoji(MessageId::new(1), &emoji("a")), 0);
+        assert_eq!(cache.order.len(), 1);
+        assert_eq!(cache.remove_emoji(MessageId::new(1), &emoji("b")), 1);
+        assert!(cache.messages.is_empty());
+        assert!(cache.order.is_empty());
+        assert_eq!(cache.remove_emoji(MessageId::new(2), &emoji("a")), 0);
+    }
+
+    #[test]
+    fn remove_all_returns_reactions_and_frees_space() {
+        let mut cache = ReactionCache::new(1);
+        cache.add(MessageId::new(1), emoji("a"));
+        cache.add(MessageId::new(1), emoji("b"));
+        assert_eq!(
+            cache.remove_all(MessageId::new(1)),
+            HashMap::from([(emoji("a"), 1), (emoji("b"), 1)])
+        );
+        assert!(cache.remove_all(MessageId::new(1)).is_empty());
+        // The pruned message no longer takes up space
+        assert_eq!(cache.add(MessageId::new(2), emoji("a")), None);
+    }
+}
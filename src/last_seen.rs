@@ -0,0 +1,223 @@
+//! This module implements the last seen tracking of members. The last activity of each member is kept in a store that
+//! is persisted to a file, such that inactive members can be detected across restarts.
+//!
+//! The store is kept apart from the cached users of the Discord handler: that cache only holds members with a known
+//! presence and is cleared whenever a guild is (re)created, while the last activity has to cover offline members and
+//! survive both. The store is also shared with the metrics server to list the inactive members.
+
+use crate::persist;
+use serenity::all::UserId;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use tracing::{info, instrument, warn};
+
+/// The thresholds (in days) after which members are counted as inactive.
+pub const INACTIVE_DAYS: [u64; 3] = [30, 90, 180];
+
+/// The number of seconds per day.
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// [`LastSeen`] is the store of the last activity (in seconds since the Unix epoch) of all members. Members that were
+/// never active are stored with the time they were first observed.
+pub struct LastSeen {
+    path: PathBuf,
+    members: RwLock<HashMap<UserId, u64>>,
+}
+
+impl LastSeen {
+    /// Loads the [`LastSeen`] store from a file. If the file does not exist yet, the store starts empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be read or is invalid.
+    #[instrument]
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let members = match std::fs::read_to_string(path) {
+            Ok(content) => parse(&content)?,
+            Err(why) if why.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(why) => return Err(why.into()),
+        };
+        info!(members = members.len(), "Loaded last seen members");
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            members: RwLock::new(members),
+        })
+    }
+
+    /// Records the activity of a member.
+    pub async fn touch(&self, user_id: UserId, now: u64) {
+        self.members.write().await.insert(user_id, now);
+    }
+
+    /// Adds members that are not yet stored. They are treated as if they were active now.
+    pub async fn observe(&self, user_ids: impl IntoIterator<Item = UserId>, now: u64) {
+        let mut members = self.members.write().await;
+        for user_id in user_ids {
+            members.entry(user_id).or_insert(now);
+        }
+    }
+
+    /// Removes a member (that left the guild).
+    pub async fn remove(&self, user_id: UserId) {
+        self.members.write().await.remove(&user_id);
+    }
+
+    /// Removes all members that are not retained (no longer on the guild).
+    pub async fn retain(&self, mut retain: impl FnMut(&UserId) -> bool) {
+        self.members
+            .write()
+            .await
+            .retain(|user_id, _| retain(user_id));
+    }
+
+    /// Gets the ids of all members that were inactive for at least the number of days (in ascending order).
+    pub async fn inactive(&self, days: u64, now: u64) -> Vec<UserId> {
+        let since = now.saturating_sub(days.saturating_mul(DAY_SECONDS));
+        let mut inactive: Vec<UserId> = self
+            .members
+            .read()
+            .await
+            .iter()
+            .filter(|(_, last_seen)| **last_seen <= since)
+            .map(|(user_id, _)| *user_id)
+            .collect();
+        inactive.sort_unstable();
+        inactive
+    }
+
+    /// Gets the number of inactive members per threshold (in days).
+    pub async fn counts(&self, now: u64) -> Vec<(u64, usize)> {
+        let members = self.members.read().await;
+        INACTIVE_DAYS
+            .iter()
+            .map(|days| {
+                let since = now.saturating_sub(days * DAY_SECONDS);
+                let count = members
+                    .values()
+                    .filter(|last_seen| **last_seen <= since)
+                    .count();
+                (*days, count)
+            })
+            .collect()
+    }
+
+    /// Persists the store to its file. The members are copied first, such that no lock is held while writing. Failures
+    /// are only logged.
+    pub async fn save(&self) {
+        let content: String = self
+            .members
+            .read()
+            .await
+            .iter()
+            .map(|(user_id, last_seen)| format!("{user_id} {last_seen}\n"))
+            .collect();
+        let path = self.path.clone();
+        if let Err(why) = persist::save(path, content.into_bytes()).await {
+            warn!(err = %why, "Failed to save last seen members");
+        }
+    }
+}
+
+/// Parses the lines (`user_id last_seen`) of a last seen file.
+fn parse(content: &str) -> Result<HashMap<UserId, u64>, Box<dyn std::error::Error>> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (user_id, last_seen) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("invalid last seen line: {line}"))?;
+            let user_id: u64 = user_id.trim().parse()?;
+            if user_id == 0 {
+                return Err(format!("invalid last seen user id: {line}").into());
+            }
+            Ok((UserId::new(user_id), last_seen.trim().parse()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty store whose file does not exist.
+    fn store(name: &str) -> LastSeen {
+        let path = std::env::temp_dir().join(format!("last-seen-{}-{name}", std::process::id()));
+        LastSeen::load(&path).expect("expected to load")
+    }
+
+    #[test]
+    fn parses_lines() {
+        let members = parse("1 100\n\n2 200\n").expect("expected to parse");
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[&UserId::new(1)], 100);
+        assert_eq!(members[&UserId::new(2)], 200);
+    }
+
+    #[test]
+    fn parses_empty_file() {
+        assert!(parse("").expect("expected to parse").is_empty());
+        assert!(parse("\n  \n").expect("expected to parse").is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(parse("1").is_err());
+        assert!(parse("a 100").is_err());
+        assert!(parse("1 b").is_err());
+        assert!(parse("0 100").is_err());
+        assert!(parse("1 -100").is_err());
+    }
+
+    #[tokio::test]
+    async fn inactive_includes_threshold() {
+        let last_seen = store("inactive");
+        let now = 100 * DAY_SECONDS;
+        last_seen
+            .touch(UserId::new(1), now - 30 * DAY_SECONDS)
+            .await;
+        last_seen
+            .touch(UserId::new(2), now - 30 * DAY_SECONDS + 1)
+            .await;
+        last_seen
+            .touch(UserId::new(3), now - 31 * DAY_SECONDS)
+            .await;
+        assert_eq!(
+            last_seen.inactive(30, now).await,
+            vec![UserId::new(1), UserId::new(3)]
+        );
+        assert!(last_seen.inactive(u64::MAX, now).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn counts_per_threshold() {
+        let last_seen = store("counts");
+        let now = 365 * DAY_SECONDS;
+        for (user_id, days) in [(1, 0), (2, 30), (3, 89), (4, 90), (5, 180), (6, 200)] {
+            last_seen
+                .touch(UserId::new(user_id), now - days * DAY_SECONDS)
+                .await;
+        }
+        assert_eq!(
+            last_seen.counts(now).await,
+            vec![(30, 5), (90, 3), (180, 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn observe_keeps_known_members() {
+        let last_seen = store("observe");
+        last_seen.touch(UserId::new(1), 10).await;
+        last_seen
+            .observe([UserId::new(1), UserId::new(2)], 20)
+            .await;
+        assert_eq!(last_seen.inactive(0, 15).await, vec![UserId::new(1)]);
+        assert_eq!(
+            last_seen.inactive(0, 20).await,
+            vec![UserId::new(1), UserId::new(2)]
+        );
+    }
+}
@@ -3,18 +3,40 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+mod active;
+mod activity;
+mod collector;
 mod discord;
+mod emoji;
+mod last_seen;
 mod metrics;
+mod patterns;
+mod peak;
+mod persist;
+mod raid;
+mod script;
+mod voice;
 
+pub use collector::Collector;
+pub use discord::MessageShape;
+pub use prometheus_client;
+pub use serenity;
+
+use last_seen::LastSeen;
+use patterns::Patterns;
+use script::ScriptCollector;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::select;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 use tracing::{error, info, warn};
 
-/// Starts the application discord listener and metrics server. The application also implements a graceful shutdown
-/// procedure that will stop the subtasks and wait for them to finish.
+/// Starts the application discord listener and metrics server with the default configuration.
+///
+/// See [`Builder`] for further configuration options.
 ///
 /// # Errors
 ///
@@ -23,68 +45,326 @@ pub async fn start(
     address: SocketAddr,
     discord_token: String,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Create metrics handler
-    let metrics_handler = Arc::new(metrics::Handler::new());
-
-    // Create discord handler (wrapping the metrics handler)
-    let discord_handler = discord::Handler::new(Arc::clone(&metrics_handler));
-
-    // Create tracker and cancellation token, they are used to implement a graceful shutdown for the handlers
-    let tracker = TaskTracker::new();
-    let token = CancellationToken::new();
-
-    // Start discord handler
-    {
-        // Shadow tracker and token for move
-        let tracker = tracker.clone();
-        let token = token.clone();
-        // Spawn task in tracker
-        tracker.clone().spawn(async move {
-            info!("Starting discord handler");
-            if let Err(why) = discord::serve(&discord_token, discord_handler, token.clone()).await {
-                error!(err = why, "Discord handler aborted");
-            }
-            info!("Stopped discord handler");
-            tracker.close();
-            token.cancel();
-        });
-    }
-
-    // Start metrics handler
-    {
-        // Shadow tracker and token for move
-        let tracker = tracker.clone();
-        let token = token.clone();
-        // Spawn task in tracker
-        tracker.clone().spawn(async move {
-            info!("Starting metrics handler");
-            if let Err(why) = metrics::serve(&address, metrics_handler, token.clone()).await {
-                error!(err = why, "Metrics handler aborted");
-            }
-            info!("Stopped metrics handler");
-            tracker.close();
-            token.cancel();
-        });
+    Builder::new(address, discord_token).start().await
+}
+
+/// [`Builder`] configures the application before it is started. It can be used to extend the exporter with custom
+/// [collectors](Collector).
+pub struct Builder {
+    address: SocketAddr,
+    discord_token: String,
+    collectors: Vec<Arc<dyn Collector>>,
+    script_file: Option<PathBuf>,
+    patterns_file: Option<PathBuf>,
+    last_seen_file: Option<PathBuf>,
+    inactive_token: Option<String>,
+    discord_config: discord::Config,
+}
+
+impl Builder {
+    /// Creates a new [`Builder`] with the required metrics server address and Discord bot token.
+    pub fn new(address: SocketAddr, discord_token: String) -> Self {
+        Self {
+            address,
+            discord_token,
+            collectors: Vec::new(),
+            script_file: None,
+            patterns_file: None,
+            last_seen_file: None,
+            inactive_token: None,
+            discord_config: discord::Config::default(),
+        }
+    }
+
+    /// Registers a custom [`Collector`]. Its metrics are served alongside the built-in metrics.
+    #[must_use]
+    pub fn collector(mut self, collector: impl Collector + 'static) -> Self {
+        self.collectors.push(Arc::new(collector));
+        self
+    }
+
+    /// Sets the script file that declares and updates custom metrics. The script is loaded on start.
+    #[must_use]
+    pub fn script_file(mut self, script_file: Option<PathBuf>) -> Self {
+        self.script_file = script_file;
+        self
+    }
+
+    /// Sets the file of named message patterns that are counted. The file is reloaded whenever it changes.
+    #[must_use]
+    pub fn patterns_file(mut self, patterns_file: Option<PathBuf>) -> Self {
+        self.patterns_file = patterns_file;
+        self
+    }
+
+    /// Sets the file that the last activity of members is persisted to. If set, the `member_inactive` metric is
+    /// enabled. By default, the last activity is not tracked.
+    #[must_use]
+    pub fn last_seen_file(mut self, last_seen_file: Option<PathBuf>) -> Self {
+        self.last_seen_file = last_seen_file;
+        self
+    }
+
+    /// Sets the bearer token that the `/inactive/{days}` endpoint requires. The endpoint is only served if the token
+    /// and the last seen file are set. By default, the endpoint is disabled.
+    #[must_use]
+    pub fn inactive_token(mut self, inactive_token: Option<String>) -> Self {
+        self.inactive_token = inactive_token;
+        self
+    }
+
+    /// Sets the number of recently reacted messages whose active reactions are tracked. The `reaction_active` metric
+    /// is disabled if the size is zero (default).
+    #[must_use]
+    pub fn reaction_cache_size(mut self, reaction_cache_size: usize) -> Self {
+        self.discord_config.reaction_cache_size = reaction_cache_size;
+        self
+    }
+
+    /// Sets the enabled [message shape](MessageShape) metrics. By default, no message shape metrics are enabled.
+    #[must_use]
+    pub fn message_shapes(
+        mut self,
+        message_shapes: impl IntoIterator<Item = MessageShape>,
+    ) -> Self {
+        self.discord_config.message_shapes = message_shapes.into_iter().collect();
+        self
+    }
+
+    /// Sets whether messages of system users are tracked. By default, only messages of regular users are tracked.
+    #[must_use]
+    pub fn system_messages(mut self, system_messages: bool) -> Self {
+        self.discord_config.system_messages = system_messages;
+        self
+    }
+
+    /// Sets the bot, webhook or application ids whose messages are tracked in the `bot_message_sent` metric. By
+    /// default, no bot messages are tracked.
+    #[must_use]
+    pub fn bot_allowlist(mut self, bot_allowlist: impl IntoIterator<Item = u64>) -> Self {
+        self.discord_config.bot_allowlist = bot_allowlist.into_iter().collect();
+        self
+    }
+
+    /// Sets the number of member joins within the raid window at which a raid is suspected. The raid detection is
+    /// disabled if the threshold is zero (default).
+    #[must_use]
+    pub fn raid_threshold(mut self, raid_threshold: usize) -> Self {
+        self.discord_config.raid_threshold = raid_threshold;
+        self
+    }
+
+    /// Sets the length of the rolling window in which member joins are counted for the raid detection. The window is
+    /// 60 seconds by default.
+    #[must_use]
+    pub fn raid_window(mut self, raid_window: Duration) -> Self {
+        self.discord_config.raid_window = raid_window;
+        self
+    }
+
+    /// Sets the URL that raid alerts are posted to whenever a raid is suspected or no longer suspected. By default, no
+    /// alerts are sent.
+    #[must_use]
+    pub fn raid_webhook(mut self, raid_webhook: Option<String>) -> Self {
+        self.discord_config.raid_webhook = raid_webhook;
+        self
+    }
+
+    /// Sets the maximum age (in days) of accounts that are counted in the `member_joined_young` metric. The metric is
+    /// disabled if the age is zero (default).
+    #[must_use]
+    pub fn young_account_days(mut self, young_account_days: u64) -> Self {
+        self.discord_config.young_account_days = young_account_days;
+        self
+    }
+
+    /// Sets the tier role ids in descending order. If set, the `member_status`, `member_voice` and `message_sent`
+    /// metrics are labelled with the highest tier role of the member. By default, no tier roles are used.
+    #[must_use]
+    pub fn tier_roles(mut self, tier_roles: impl IntoIterator<Item = u64>) -> Self {
+        self.discord_config.tier_roles = tier_roles.into_iter().collect();
+        self
+    }
+
+    /// Sets whether members in the AFK channel of the guild are excluded from the `member_voice` metric. By default,
+    /// the AFK channel is included.
+    #[must_use]
+    pub fn exclude_afk(mut self, exclude_afk: bool) -> Self {
+        self.discord_config.exclude_afk = exclude_afk;
+        self
+    }
+
+    /// Sets whether custom statuses are excluded from the `activity` metric. By default, custom statuses are included.
+    #[must_use]
+    pub fn exclude_custom_status(mut self, exclude_custom_status: bool) -> Self {
+        self.discord_config.exclude_custom_status = exclude_custom_status;
+        self
     }
 
-    // Listen for system shutdown signal (in main thread)
-    info!("Listening for signal received");
-    select! {
-        _ = tokio::signal::ctrl_c() => {
-            info!("Shutdown signal received");
+    /// Sets the aliases (activity name to alias) that activity names are merged through to lower the cardinality of
+    /// the `activity` metric. By default, no aliases are used.
+    #[must_use]
+    pub fn activity_aliases(
+        mut self,
+        activity_aliases: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        self.discord_config.activity_aliases = activity_aliases.into_iter().collect();
+        self
+    }
+
+    /// Sets the file that the sketches of the `active_users` metric are persisted to, such that they survive restarts.
+    /// By default, the active users are only kept in memory.
+    #[must_use]
+    pub fn active_users_file(mut self, active_users_file: Option<PathBuf>) -> Self {
+        self.discord_config.active_users_file = active_users_file;
+        self
+    }
+
+    /// Starts the application discord listener and metrics server. The application also implements a graceful
+    /// shutdown procedure that will stop the subtasks and wait for them to finish.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script, patterns or last seen file could not be loaded. Any other error is only logged.
+    pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
+        let Self {
+            address,
+            discord_token,
+            mut collectors,
+            script_file,
+            patterns_file,
+            last_seen_file,
+            inactive_token,
+            discord_config,
+        } = self;
+
+        // Load script (as a collector)
+        if let Some(script_file) = script_file {
+            collectors.push(Arc::new(ScriptCollector::load(&script_file)?));
+        }
+
+        // Load message patterns
+        let patterns = patterns_file
+            .map(|patterns_file| Patterns::load(&patterns_file))
+            .transpose()?
+            .map(Arc::new);
+
+        // Load last seen members
+        let last_seen = last_seen_file
+            .map(|last_seen_file| LastSeen::load(&last_seen_file))
+            .transpose()?
+            .map(Arc::new);
+
+        // Create metrics handler (including the metrics of the collectors)
+        let metrics_handler = Arc::new(metrics::Handler::new(&collectors));
+
+        // Create discord handler (wrapping the metrics handler)
+        let discord_handler = Arc::new(discord::Handler::new(
+            Arc::clone(&metrics_handler),
+            patterns.clone(),
+            last_seen.clone(),
+            discord_config,
+        ));
+
+        // Create tracker and cancellation token, they are used to implement a graceful shutdown for the handlers
+        let tracker = TaskTracker::new();
+        let token = CancellationToken::new();
+
+        // Start discord handler
+        {
+            // Shadow tracker, token and collectors for move
+            let tracker = tracker.clone();
+            let token = token.clone();
+            let collectors = collectors.clone();
+            let discord_handler = Arc::clone(&discord_handler);
+            // Spawn task in tracker
+            tracker.clone().spawn(async move {
+                info!("Starting discord handler");
+                if let Err(why) =
+                    discord::serve(&discord_token, discord_handler, collectors, token.clone()).await
+                {
+                    error!(err = why, "Discord handler aborted");
+                }
+                info!("Stopped discord handler");
+                tracker.close();
+                token.cancel();
+            });
         }
-        // Explicitly wait for token cancellation such that errors from the handlers
-        // result in an application shutdown
-        () = token.cancelled() => {
-            warn!("System shutdown before shutdown signal received");
+
+        // Start metrics handler
+        {
+            // Shadow tracker and token for move
+            let tracker = tracker.clone();
+            let token = token.clone();
+            // Spawn task in tracker
+            tracker.clone().spawn(async move {
+                info!("Starting metrics handler");
+                if let Err(why) = metrics::serve(
+                    &address,
+                    metrics_handler,
+                    last_seen,
+                    inactive_token,
+                    token.clone(),
+                )
+                .await
+                {
+                    error!(err = why, "Metrics handler aborted");
+                }
+                info!("Stopped metrics handler");
+                tracker.close();
+                token.cancel();
+            });
+        }
+
+        // Start discord refresh
+        {
+            // Shadow token for move
+            let token = token.clone();
+            // Spawn task in tracker
+            tracker.spawn(async move {
+                discord_handler.run(token).await;
+            });
         }
-    }
-    tracker.close();
-    token.cancel();
 
-    // Wait for all tasks to finish (graceful shutdown)
-    tracker.wait().await;
-    info!("Shutdown successfully");
+        // Start message patterns watcher
+        if let Some(patterns) = patterns {
+            // Shadow token for move
+            let token = token.clone();
+            // Spawn task in tracker
+            tracker.spawn(async move {
+                patterns.watch(token).await;
+            });
+        }
 
-    Ok(())
+        // Start collectors
+        for collector in collectors {
+            // Shadow token for move
+            let token = token.clone();
+            // Spawn task in tracker
+            tracker.spawn(async move {
+                collector.run(token).await;
+            });
+        }
+
+        // Listen for system shutdown signal (in main thread)
+        info!("Listening for signal received");
+        select! {
+            _ = tokio::signal::ctrl_c() => {
+                info!("Shutdown signal received");
+            }
+            // Explicitly wait for token cancellation such that errors from the handlers
+            // result in an application shutdown
+            () = token.cancelled() => {
+                warn!("System shutdown before shutdown signal received");
+            }
+        }
+        tracker.close();
+        token.cancel();
+
+        // Wait for all tasks to finish (graceful shutdown)
+        tracker.wait().await;
+        info!("Shutdown successfully");
+
+        Ok(())
+    }
 }
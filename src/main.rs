@@ -1,7 +1,10 @@
 use clap::Parser;
+use dcexport::MessageShape;
 use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
@@ -45,6 +48,48 @@ struct Args {
     log_level: Log,
     #[arg(long, env, default_value = DEFAULT_ADDRESS)]
     address: SocketAddr,
+    #[arg(long, env)]
+    script_file: Option<PathBuf>,
+    #[arg(long, env)]
+    patterns_file: Option<PathBuf>,
+    #[arg(long, env)]
+    last_seen_file: Option<PathBuf>,
+    #[arg(long, env)]
+    inactive_token: Option<String>,
+    #[arg(long, env, default_value_t = 0)]
+    reaction_cache_size: usize,
+    #[arg(long, env, value_delimiter = ',', value_parser = MessageShape::from_str)]
+    message_shapes: Vec<MessageShape>,
+    #[arg(long, env)]
+    system_messages: bool,
+    #[arg(long, env, value_delimiter = ',')]
+    bot_allowlist: Vec<u64>,
+    #[arg(long, env, default_value_t = 0)]
+    raid_threshold: usize,
+    #[arg(long, env, default_value_t = 60)]
+    raid_window: u64,
+    #[arg(long, env)]
+    raid_webhook: Option<String>,
+    #[arg(long, env, default_value_t = 0)]
+    young_account_days: u64,
+    #[arg(long, env, value_delimiter = ',')]
+    tier_roles: Vec<u64>,
+    #[arg(long, env)]
+    exclude_afk: bool,
+    #[arg(long, env)]
+    exclude_custom_status: bool,
+    #[arg(long, env, value_delimiter = ',', value_parser = parse_alias)]
+    activity_aliases: Vec<(String, String)>,
+    #[arg(long, env)]
+    active_users_file: Option<PathBuf>,
+}
+
+/// Parses an activity alias of the form `name=alias`.
+fn parse_alias(s: &str) -> Result<(String, String), String> {
+    let (name, alias) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid activity alias (expected name=alias): {s}"))?;
+    Ok((name.trim().to_string(), alias.trim().to_string()))
 }
 
 /// Initializes the application and invokes dcexport.
@@ -67,5 +112,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .enable_all()
         .build()
         .unwrap()
-        .block_on(async { dcexport::start(args.address, args.discord_token).await })
+        .block_on(async {
+            dcexport::Builder::new(args.address, args.discord_token)
+                .script_file(args.script_file)
+                .patterns_file(args.patterns_file)
+                .last_seen_file(args.last_seen_file)
+                .inactive_token(args.inactive_token)
+                .reaction_cache_size(args.reaction_cache_size)
+                .message_shapes(args.message_shapes)
+                .system_messages(args.system_messages)
+                .bot_allowlist(args.bot_allowlist)
+                .raid_threshold(args.raid_threshold)
+                .raid_window(Duration::from_secs(args.raid_window))
+                .raid_webhook(args.raid_webhook)
+                .young_account_days(args.young_account_days)
+                .tier_roles(args.tier_roles)
+                .exclude_afk(args.exclude_afk)
+                .exclude_custom_status(args.exclude_custom_status)
+                .activity_aliases(args.activity_aliases)
+                .active_users_file(args.active_users_file)
+                .start()
+                .await
+        })
 }
@@ -1,8 +1,13 @@
 //! This module implements the metrics handler and its http server.
 
+use crate::activity::now_millis;
+use crate::collector::Collector;
+use crate::emoji::ReactionEmoji;
+use crate::last_seen::LastSeen;
 use axum::body::Body;
-use axum::http::StatusCode;
-use axum::http::header::CONTENT_TYPE;
+use axum::extract::Path;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{Html, Response};
 use axum::routing::get;
 use axum::{Extension, Router};
@@ -11,12 +16,24 @@ use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue, LabelValueEn
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
 use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::Histogram;
 use prometheus_client::registry::Registry;
 use serenity::all::{
-    Activity, ApplicationId, ChannelId, EmojiId, Guild, GuildChannel, OnlineStatus, VoiceState,
+    Activity, ActivityType, ApplicationId, ChannelId, EmojiId, Guild, GuildChannel, Message,
+    MessageType, OnlineStatus, Role, RoleId, VoiceState,
 };
+use serenity::model::guild::audit_log::{
+    Action, AutoModAction, ChannelAction, ChannelOverwriteAction, CreatorMonetizationAction,
+    EmojiAction, IntegrationAction, InviteAction, MemberAction, MessageAction, RoleAction,
+    ScheduledEventAction, StageInstanceAction, StickerAction, ThreadAction,
+    VoiceChannelStatusAction, WebhookAction,
+};
+use serenity::model::guild::automod;
+use serenity::model::guild::automod::{ActionExecution, Rule, Trigger};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
 use tokio_util::sync::CancellationToken;
 use tower_http::trace::TraceLayer;
 use tracing::{debug, instrument, trace};
@@ -53,6 +70,163 @@ impl EncodeLabelValue for Boolean {
     }
 }
 
+/// Gets the `snake_case` name of a [`MessageType`]. Unknown types are named `unknown`.
+pub fn message_kind(kind: MessageType) -> &'static str {
+    match kind {
+        MessageType::Regular => "regular",
+        MessageType::GroupRecipientAddition => "group_recipient_addition",
+        MessageType::GroupRecipientRemoval => "group_recipient_removal",
+        MessageType::GroupCallCreation => "group_call_creation",
+        MessageType::GroupNameUpdate => "group_name_update",
+        MessageType::GroupIconUpdate => "group_icon_update",
+        MessageType::PinsAdd => "pins_add",
+        MessageType::MemberJoin => "member_join",
+        MessageType::NitroBoost => "nitro_boost",
+        MessageType::NitroTier1 => "nitro_tier1",
+        MessageType::NitroTier2 => "nitro_tier2",
+        MessageType::NitroTier3 => "nitro_tier3",
+        MessageType::ChannelFollowAdd => "channel_follow_add",
+        MessageType::GuildDiscoveryDisqualified => "guild_discovery_disqualified",
+        MessageType::GuildDiscoveryRequalified => "guild_discovery_requalified",
+        MessageType::GuildDiscoveryGracePeriodInitialWarning => {
+            "guild_discovery_grace_period_initial_warning"
+        }
+        MessageType::GuildDiscoveryGracePeriodFinalWarning => {
+            "guild_discovery_grace_period_final_warning"
+        }
+        MessageType::ThreadCreated => "thread_created",
+        MessageType::InlineReply => "inline_reply",
+        MessageType::ChatInputCommand => "chat_input_command",
+        MessageType::ThreadStarterMessage => "thread_starter_message",
+        MessageType::GuildInviteReminder => "guild_invite_reminder",
+        MessageType::ContextMenuCommand => "context_menu_command",
+        MessageType::AutoModAction => "auto_mod_action",
+        MessageType::RoleSubscriptionPurchase => "role_subscription_purchase",
+        MessageType::InteractionPremiumUpsell => "interaction_premium_upsell",
+        MessageType::StageStart => "stage_start",
+        MessageType::StageEnd => "stage_end",
+        MessageType::StageSpeaker => "stage_speaker",
+        MessageType::StageTopic => "stage_topic",
+        MessageType::GuildApplicationPremiumSubscription => {
+            "guild_application_premium_subscription"
+        }
+        MessageType::GuildIncidentAlertModeEnabled => "guild_incident_alert_mode_enabled",
+        MessageType::GuildIncidentAlertModeDisabled => "guild_incident_alert_mode_disabled",
+        MessageType::GuildIncidentReportRaid => "guild_incident_report_raid",
+        MessageType::GuildIncidentReportFalseAlarm => "guild_incident_report_false_alarm",
+        _ => "unknown",
+    }
+}
+
+/// Gets the `snake_case` name of an audit log [`Action`]. The names of nested actions are joined (e.g. `member_prune`).
+/// Unknown actions are named `unknown`.
+pub fn audit_log_action(action: Action) -> &'static str {
+    match action {
+        Action::GuildUpdate => "guild_update",
+        Action::Channel(ChannelAction::Create) => "channel_create",
+        Action::Channel(ChannelAction::Update) => "channel_update",
+        Action::Channel(ChannelAction::Delete) => "channel_delete",
+        Action::ChannelOverwrite(ChannelOverwriteAction::Create) => "channel_overwrite_create",
+        Action::ChannelOverwrite(ChannelOverwriteAction::Update) => "channel_overwrite_update",
+        Action::ChannelOverwrite(ChannelOverwriteAction::Delete) => "channel_overwrite_delete",
+        Action::Member(MemberAction::Kick) => "member_kick",
+        Action::Member(MemberAction::Prune) => "member_prune",
+        Action::Member(MemberAction::BanAdd) => "member_ban_add",
+        Action::Member(MemberAction::BanRemove) => "member_ban_remove",
+        Action::Member(MemberAction::Update) => "member_update",
+        Action::Member(MemberAction::RoleUpdate) => "member_role_update",
+        Action::Member(MemberAction::MemberMove) => "member_member_move",
+        Action::Member(MemberAction::MemberDisconnect) => "member_member_disconnect",
+        Action::Member(MemberAction::BotAdd) => "member_bot_add",
+        Action::Role(RoleAction::Create) => "role_create",
+        Action::Role(RoleAction::Update) => "role_update",
+        Action::Role(RoleAction::Delete) => "role_delete",
+        Action::Invite(InviteAction::Create) => "invite_create",
+        Action::Invite(InviteAction::Update) => "invite_update",
+        Action::Invite(InviteAction::Delete) => "invite_delete",
+        Action::Webhook(WebhookAction::Create) => "webhook_create",
+        Action::Webhook(WebhookAction::Update) => "webhook_update",
+        Action::Webhook(WebhookAction::Delete) => "webhook_delete",
+        Action::Emoji(EmojiAction::Create) => "emoji_create",
+        Action::Emoji(EmojiAction::Update) => "emoji_update",
+        Action::Emoji(EmojiAction::Delete) => "emoji_delete",
+        Action::Message(MessageAction::Delete) => "message_delete",
+        Action::Message(MessageAction::BulkDelete) => "message_bulk_delete",
+        Action::Message(MessageAction::Pin) => "message_pin",
+        Action::Message(MessageAction::Unpin) => "message_unpin",
+        Action::Integration(IntegrationAction::Create) => "integration_create",
+        Action::Integration(IntegrationAction::Update) => "integration_update",
+        Action::Integration(IntegrationAction::Delete) => "integration_delete",
+        Action::StageInstance(StageInstanceAction::Create) => "stage_instance_create",
+        Action::StageInstance(StageInstanceAction::Update) => "stage_instance_update",
+        Action::StageInstance(StageInstanceAction::Delete) => "stage_instance_delete",
+        Action::Sticker(StickerAction::Create) => "sticker_create",
+        Action::Sticker(StickerAction::Update) => "sticker_update",
+        Action::Sticker(StickerAction::Delete) => "sticker_delete",
+        Action::ScheduledEvent(ScheduledEventAction::Create) => "scheduled_event_create",
+        Action::ScheduledEvent(ScheduledEventAction::Update) => "scheduled_event_update",
+        Action::ScheduledEvent(ScheduledEventAction::Delete) => "scheduled_event_delete",
+        Action::Thread(ThreadAction::Create) => "thread_create",
+        Action::Thread(ThreadAction::Update) => "thread_update",
+        Action::Thread(ThreadAction::Delete) => "thread_delete",
+        Action::AutoMod(AutoModAction::RuleCreate) => "auto_mod_rule_create",
+        Action::AutoMod(AutoModAction::RuleUpdate) => "auto_mod_rule_update",
+        Action::AutoMod(AutoModAction::RuleDelete) => "auto_mod_rule_delete",
+        Action::AutoMod(AutoModAction::BlockMessage) => "auto_mod_block_message",
+        Action::AutoMod(AutoModAction::FlagToChannel) => "auto_mod_flag_to_channel",
+        Action::AutoMod(AutoModAction::UserCommunicationDisabled) => {
+            "auto_mod_user_communication_disabled"
+        }
+        Action::CreatorMonetization(CreatorMonetizationAction::RequestCreated) => {
+            "creator_monetization_request_created"
+        }
+        Action::CreatorMonetization(CreatorMonetizationAction::TermsAccepted) => {
+            "creator_monetization_terms_accepted"
+        }
+        Action::VoiceChannelStatus(VoiceChannelStatusAction::StatusUpdate) => {
+            "voice_channel_status_status_update"
+        }
+        Action::VoiceChannelStatus(VoiceChannelStatusAction::StatusDelete) => {
+            "voice_channel_status_status_delete"
+        }
+        _ => "unknown",
+    }
+}
+
+/// Gets the `snake_case` name of an [`ActivityType`]. Unknown types are named `unknown`.
+pub fn activity_type(kind: ActivityType) -> &'static str {
+    match kind {
+        ActivityType::Playing => "playing",
+        ActivityType::Streaming => "streaming",
+        ActivityType::Listening => "listening",
+        ActivityType::Watching => "watching",
+        ActivityType::Custom => "custom",
+        ActivityType::Competing => "competing",
+        _ => "unknown",
+    }
+}
+
+/// Gets the `snake_case` name of an AutoMod [`automod::Action`]. Unknown actions are named `unknown`.
+pub fn automod_action_type(action: &automod::Action) -> &'static str {
+    match action {
+        automod::Action::BlockMessage { .. } => "block_message",
+        automod::Action::Alert(_) => "alert",
+        automod::Action::Timeout(_) => "timeout",
+        _ => "unknown",
+    }
+}
+
+/// Gets the `snake_case` name of an AutoMod [`Trigger`]. Unknown triggers are named `unknown`.
+pub fn automod_trigger_type(trigger: &Trigger) -> &'static str {
+    match trigger {
+        Trigger::Keyword { .. } => "keyword",
+        Trigger::Spam => "spam",
+        Trigger::KeywordPreset { .. } => "keyword_preset",
+        Trigger::MentionSpam { .. } => "mention_spam",
+        _ => "unknown",
+    }
+}
+
 /// [`GuildsLabels`] are the [labels](EncodeLabelSet) for the `guild` metric.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 pub struct GuildsLabels {
@@ -89,6 +263,76 @@ impl ChannelLabels {
     }
 }
 
+/// [`RoleLabels`] are the [labels](EncodeLabelSet) for the `role` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RoleLabels {
+    pub role_id: u64,
+    pub role_name: String,
+    pub role_color: String,
+}
+
+impl RoleLabels {
+    /// Creates a new instance of [`RoleLabels`].
+    pub fn new(role: &Role) -> Self {
+        Self {
+            role_id: role.id.get(),
+            role_name: role.name.clone(),
+            role_color: role.colour.hex(),
+        }
+    }
+}
+
+/// [`RoleMemberLabels`] are the [labels](EncodeLabelSet) for the `role_member` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RoleMemberLabels {
+    pub role_id: u64,
+}
+
+impl RoleMemberLabels {
+    /// Creates a new instance of [`RoleMemberLabels`].
+    pub fn new(role_id: RoleId) -> Self {
+        Self {
+            role_id: role_id.get(),
+        }
+    }
+}
+
+/// The buckets of the `message_length` metric (in characters).
+const MESSAGE_LENGTH_BUCKETS: [f64; 9] =
+    [1.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2000.0];
+
+/// The buckets of the `message_attachments` and `message_embeds` metrics.
+const MESSAGE_COUNT_BUCKETS: [f64; 6] = [0.0, 1.0, 2.0, 3.0, 5.0, 10.0];
+
+/// The buckets of the `member_tenure` metric (in seconds).
+const MEMBER_TENURE_BUCKETS: [f64; 9] = [
+    3_600.0,      // 1 hour
+    21_600.0,     // 6 hours
+    86_400.0,     // 1 day
+    604_800.0,    // 7 days
+    2_592_000.0,  // 30 days
+    7_776_000.0,  // 90 days
+    15_552_000.0, // 180 days
+    31_536_000.0, // 365 days
+    63_072_000.0, // 730 days
+];
+
+/// The buckets of the `voice_session_duration_seconds` metric (in seconds).
+const VOICE_SESSION_DURATION_BUCKETS: [f64; 9] = [
+    60.0,     // 1 minute
+    300.0,    // 5 minutes
+    900.0,    // 15 minutes
+    1_800.0,  // 30 minutes
+    3_600.0,  // 1 hour
+    7_200.0,  // 2 hours
+    14_400.0, // 4 hours
+    28_800.0, // 8 hours
+    86_400.0, // 1 day
+];
+
+/// A [`Histogram`] constructor for [families](Family) of histograms.
+type HistogramConstructor = fn() -> Histogram;
+
 /// [`BoostLabels`] are the [labels](EncodeLabelSet) for the `boost` metric.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 pub struct BoostLabels {}
@@ -100,84 +344,651 @@ impl BoostLabels {
     }
 }
 
-/// [`MemberLabels`] are the [labels](EncodeLabelSet) for the `member` metric.
+/// [`MemberLabels`] are the [labels](EncodeLabelSet) for the `member` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberLabels {}
+
+impl MemberLabels {
+    /// Creates a new instance of [`MemberLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberJoinedLabels`] are the [labels](EncodeLabelSet) for the `member_joined` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberJoinedLabels {}
+
+impl MemberJoinedLabels {
+    /// Creates a new instance of [`MemberJoinedLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberLeftLabels`] are the [labels](EncodeLabelSet) for the `member_left` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberLeftLabels {}
+
+impl MemberLeftLabels {
+    /// Creates a new instance of [`MemberLeftLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberTenureLabels`] are the [labels](EncodeLabelSet) for the `member_tenure` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberTenureLabels {}
+
+impl MemberTenureLabels {
+    /// Creates a new instance of [`MemberTenureLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberJoinedYoungLabels`] are the [labels](EncodeLabelSet) for the `member_joined_young` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberJoinedYoungLabels {}
+
+impl MemberJoinedYoungLabels {
+    /// Creates a new instance of [`MemberJoinedYoungLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`RaidSuspectedLabels`] are the [labels](EncodeLabelSet) for the `raid_suspected` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RaidSuspectedLabels {}
+
+impl RaidSuspectedLabels {
+    /// Creates a new instance of [`RaidSuspectedLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberBannedLabels`] are the [labels](EncodeLabelSet) for the `member_banned` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberBannedLabels {}
+
+impl MemberBannedLabels {
+    /// Creates a new instance of [`MemberBannedLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberUnbannedLabels`] are the [labels](EncodeLabelSet) for the `member_unbanned` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberUnbannedLabels {}
+
+impl MemberUnbannedLabels {
+    /// Creates a new instance of [`MemberUnbannedLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberKickedLabels`] are the [labels](EncodeLabelSet) for the `member_kicked` metric.
+///
+/// Kicks are attributed to the highest role of the moderator, if the moderator is cached.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberKickedLabels {
+    pub moderator_role_id: Option<u64>,
+}
+
+impl MemberKickedLabels {
+    /// Creates a new instance of [`MemberKickedLabels`].
+    pub fn new(moderator_role_id: Option<RoleId>) -> Self {
+        Self {
+            moderator_role_id: moderator_role_id.map(RoleId::get),
+        }
+    }
+}
+
+/// [`MemberTimeoutLabels`] are the [labels](EncodeLabelSet) for the `member_timeout` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberTimeoutLabels {}
+
+impl MemberTimeoutLabels {
+    /// Creates a new instance of [`MemberTimeoutLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberTimedOutLabels`] are the [labels](EncodeLabelSet) for the `member_timed_out` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberTimedOutLabels {}
+
+impl MemberTimedOutLabels {
+    /// Creates a new instance of [`MemberTimedOutLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`ModeratorActionLabels`] are the [labels](EncodeLabelSet) for the `moderator_action` metric.
+///
+/// Actions are attributed to the highest role of the moderator through the audit log, if the moderator is cached.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ModeratorActionLabels {
+    pub action: String,
+    pub moderator_role_id: Option<u64>,
+}
+
+impl ModeratorActionLabels {
+    /// Creates a new instance of [`ModeratorActionLabels`].
+    pub fn new(action: &str, moderator_role_id: Option<RoleId>) -> Self {
+        Self {
+            action: action.to_string(),
+            moderator_role_id: moderator_role_id.map(RoleId::get),
+        }
+    }
+}
+
+/// [`AuditLogEntryLabels`] are the [labels](EncodeLabelSet) for the `audit_log_entry` metric.
+///
+/// Entries are attributed to the highest role of the responsible user, if the user is cached.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct AuditLogEntryLabels {
+    pub action: String,
+    pub user_role_id: Option<u64>,
+}
+
+impl AuditLogEntryLabels {
+    /// Creates a new instance of [`AuditLogEntryLabels`].
+    pub fn new(action: &Action, user_role_id: Option<RoleId>) -> Self {
+        Self {
+            action: audit_log_action(*action).to_string(),
+            user_role_id: user_role_id.map(RoleId::get),
+        }
+    }
+}
+
+/// [`AutomodActionLabels`] are the [labels](EncodeLabelSet) for the `automod_action` metric.
+///
+/// The rule name is only known for rules that are part of the rule inventory.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct AutomodActionLabels {
+    pub rule_id: u64,
+    pub rule_name: Option<String>,
+    pub action_type: String,
+    pub category_id: Option<u64>,
+    pub channel_id: Option<u64>,
+}
+
+impl AutomodActionLabels {
+    /// Creates a new instance of [`AutomodActionLabels`].
+    pub fn new(
+        execution: &ActionExecution,
+        rule_name: Option<String>,
+        category_id: Option<ChannelId>,
+        channel_id: Option<ChannelId>,
+    ) -> Self {
+        Self {
+            rule_id: execution.rule_id.get(),
+            rule_name,
+            action_type: automod_action_type(&execution.action).to_string(),
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.map(ChannelId::get),
+        }
+    }
+}
+
+/// [`AutomodRuleLabels`] are the [labels](EncodeLabelSet) for the `automod_rule` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct AutomodRuleLabels {
+    pub rule_id: u64,
+    pub rule_name: String,
+    pub trigger_type: String,
+    pub enabled: Boolean,
+}
+
+impl AutomodRuleLabels {
+    /// Creates a new instance of [`AutomodRuleLabels`].
+    pub fn new(rule: &Rule) -> Self {
+        Self {
+            rule_id: rule.id.get(),
+            rule_name: rule.name.clone(),
+            trigger_type: automod_trigger_type(&rule.trigger).to_string(),
+            enabled: Boolean(rule.enabled),
+        }
+    }
+}
+
+/// [`BotLabels`] are the [labels](EncodeLabelSet) for the `bot` metric.
+///
+/// This metric is not included in the member metric (using a label) as the user bot status has to
+/// be explicitly requested on guild creation. As such, they are separated to ensure that the member
+/// metric does not suffer from additional requests (that could potentially fail).
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct BotLabels {}
+
+impl BotLabels {
+    /// Creates a new instance of [`BotLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`MemberStatusLabels`] are the [labels](EncodeLabelSet) for the `member_status` metric.
+///
+/// The tier role is the highest configured tier role of the member, if any tier roles are configured.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberStatusLabels {
+    pub status: String,
+    pub tier_role_id: Option<u64>,
+}
+
+impl MemberStatusLabels {
+    /// Creates a new instance of [`MemberStatusLabels`].
+    pub fn new(status: OnlineStatus, tier_role_id: Option<RoleId>) -> Self {
+        Self {
+            status: status.name().to_string(),
+            tier_role_id: tier_role_id.map(RoleId::get),
+        }
+    }
+}
+
+/// [`MemberClientStatusLabels`] are the [labels](EncodeLabelSet) for the `member_client_status` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberClientStatusLabels {
+    pub platform: String,
+    pub status: String,
+}
+
+impl MemberClientStatusLabels {
+    /// Creates a new instance of [`MemberClientStatusLabels`].
+    pub fn new(platform: &str, status: OnlineStatus) -> Self {
+        Self {
+            platform: platform.to_string(),
+            status: status.name().to_string(),
+        }
+    }
+}
+
+/// [`StatusTransitionLabels`] are the [labels](EncodeLabelSet) for the `status_transition` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct StatusTransitionLabels {
+    pub from: String,
+    pub to: String,
+}
+
+impl StatusTransitionLabels {
+    /// Creates a new instance of [`StatusTransitionLabels`].
+    pub fn new(from: OnlineStatus, to: OnlineStatus) -> Self {
+        Self {
+            from: from.name().to_string(),
+            to: to.name().to_string(),
+        }
+    }
+}
+
+/// [`MemberOnlinePeakLabels`] are the [labels](EncodeLabelSet) for the `member_online_peak` metric.
+///
+/// The window is either a rolling window (`1h`, `24h`) or the start of the tracking (`start`).
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberOnlinePeakLabels {
+    pub window: String,
+}
+
+impl MemberOnlinePeakLabels {
+    /// Creates a new instance of [`MemberOnlinePeakLabels`].
+    pub fn new(window: &str) -> Self {
+        Self {
+            window: window.to_string(),
+        }
+    }
+}
+
+/// [`ActiveUsersLabels`] are the [labels](EncodeLabelSet) for the `active_users` metric.
+///
+/// The window is one of the rolling windows `1h`, `24h`, `7d` and `30d` (with an hourly resolution).
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ActiveUsersLabels {
+    pub window: String,
+}
+
+impl ActiveUsersLabels {
+    /// Creates a new instance of [`ActiveUsersLabels`].
+    pub fn new(window: &str) -> Self {
+        Self {
+            window: window.to_string(),
+        }
+    }
+}
+
+/// [`MemberInactiveLabels`] are the [labels](EncodeLabelSet) for the `member_inactive` metric.
+///
+/// The days are one of the inactivity thresholds `30`, `90` and `180`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberInactiveLabels {
+    pub days: String,
+}
+
+impl MemberInactiveLabels {
+    /// Creates a new instance of [`MemberInactiveLabels`].
+    pub fn new(days: u64) -> Self {
+        Self {
+            days: days.to_string(),
+        }
+    }
+}
+
+/// [`MemberVoiceLabels`] are the [labels](EncodeLabelSet) for the `member_voice` metric.
+///
+/// The stage state (`speaker`, `audience` or `requested`) is only set for members in stage channels.
+///
+/// The tier role is the highest configured tier role of the member, if any tier roles are configured.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MemberVoiceLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub self_stream: Boolean,
+    pub self_video: Boolean,
+    pub self_deaf: Boolean,
+    pub self_mute: Boolean,
+    pub mute: Boolean,
+    pub deaf: Boolean,
+    pub suppress: Boolean,
+    pub stage_state: Option<String>,
+    pub tier_role_id: Option<u64>,
+}
+
+impl MemberVoiceLabels {
+    /// Creates a new instance of [`MemberVoiceLabels`].
+    pub fn new(
+        category_id: Option<ChannelId>,
+        channel_id: ChannelId,
+        voice: &VoiceState,
+        stage: bool,
+        tier_role_id: Option<RoleId>,
+    ) -> Self {
+        let stage_state = stage.then(|| {
+            match (voice.suppress, voice.request_to_speak_timestamp.is_some()) {
+                (false, _) => "speaker",
+                (true, true) => "requested",
+                (true, false) => "audience",
+            }
+            .to_string()
+        });
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            self_stream: voice.self_stream.unwrap_or(false).into(),
+            self_video: voice.self_video.into(),
+            self_deaf: voice.self_deaf.into(),
+            self_mute: voice.self_mute.into(),
+            mute: voice.mute.into(),
+            deaf: voice.deaf.into(),
+            suppress: voice.suppress.into(),
+            stage_state,
+            tier_role_id: tier_role_id.map(RoleId::get),
+        }
+    }
+}
+
+/// [`VoiceSecondsLabels`] are the [labels](EncodeLabelSet) for the `voice_seconds` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct VoiceSecondsLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+}
+
+impl VoiceSecondsLabels {
+    /// Creates a new instance of [`VoiceSecondsLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+        }
+    }
+}
+
+/// [`VoiceSessionDurationLabels`] are the [labels](EncodeLabelSet) for the `voice_session_duration_seconds` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct VoiceSessionDurationLabels {}
+
+impl VoiceSessionDurationLabels {
+    /// Creates a new instance of [`VoiceSessionDurationLabels`].
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// [`StageInstanceLabels`] are the [labels](EncodeLabelSet) for the `stage_instance` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct StageInstanceLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+}
+
+impl StageInstanceLabels {
+    /// Creates a new instance of [`StageInstanceLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+        }
+    }
+}
+
+/// [`VoiceChannelCapacityLabels`] are the [labels](EncodeLabelSet) for the `voice_channel_capacity` metric.
+///
+/// The value of the metric is the user limit of the channel (`0` if unlimited).
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct VoiceChannelCapacityLabels {
+    pub channel_id: u64,
+    pub bitrate: Option<u32>,
+    pub rtc_region: Option<String>,
+}
+
+impl VoiceChannelCapacityLabels {
+    /// Creates a new instance of [`VoiceChannelCapacityLabels`].
+    pub fn new(channel: &GuildChannel) -> Self {
+        Self {
+            channel_id: channel.id.get(),
+            bitrate: channel.bitrate,
+            rtc_region: channel.rtc_region.clone(),
+        }
+    }
+}
+
+/// [`MessageSentLabels`] are the [labels](EncodeLabelSet) for the `message_sent` metric.
+///
+/// The tier role is the highest configured tier role of the member, if any tier roles are configured.
+#[allow(clippy::struct_field_names)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MessageSentLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub tier_role_id: Option<u64>,
+}
+
+impl MessageSentLabels {
+    /// Creates a new instance of [`MessageSentLabels`].
+    pub fn new(
+        category_id: Option<ChannelId>,
+        channel_id: ChannelId,
+        tier_role_id: Option<RoleId>,
+    ) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            tier_role_id: tier_role_id.map(RoleId::get),
+        }
+    }
+}
+
+/// [`BotMessageSentLabels`] are the [labels](EncodeLabelSet) for the `bot_message_sent` metric.
+///
+/// The author of webhook messages is the webhook itself.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct BotMessageSentLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub author_id: u64,
+    pub webhook: Boolean,
+    pub application_id: Option<u64>,
+}
+
+impl BotMessageSentLabels {
+    /// Creates a new instance of [`BotMessageSentLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, msg: &Message) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            author_id: msg.author.id.get(),
+            webhook: Boolean(msg.webhook_id.is_some()),
+            application_id: msg.application_id.map(ApplicationId::get),
+        }
+    }
+}
+
+/// [`MessageKindLabels`] are the [labels](EncodeLabelSet) for the `message_kind` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MessageKindLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub kind: String,
+}
+
+impl MessageKindLabels {
+    /// Creates a new instance of [`MessageKindLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, kind: MessageType) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            kind: message_kind(kind).to_string(),
+        }
+    }
+}
+
+/// [`MessageShapeLabels`] are the [labels](EncodeLabelSet) for the `message_length`, `message_attachments`,
+/// `message_embeds` and `message_reply` metrics.
+#[allow(clippy::struct_field_names)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MessageShapeLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+}
+
+impl MessageShapeLabels {
+    /// Creates a new instance of [`MessageShapeLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+        }
+    }
+}
+
+/// [`MessageAttachmentTypeLabels`] are the [labels](EncodeLabelSet) for the `message_attachment_type` metric.
+///
+/// The content type is reduced to its category (`image`, `video`, `audio` or `other`).
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
-pub struct MemberLabels {}
+pub struct MessageAttachmentTypeLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub content_type: String,
+}
 
-impl MemberLabels {
-    /// Creates a new instance of [`MemberLabels`].
-    pub fn new() -> Self {
-        Self {}
+impl MessageAttachmentTypeLabels {
+    /// Creates a new instance of [`MessageAttachmentTypeLabels`].
+    pub fn new(
+        category_id: Option<ChannelId>,
+        channel_id: ChannelId,
+        content_type: Option<&str>,
+    ) -> Self {
+        let content_type = content_type
+            .and_then(|content_type| content_type.split('/').next())
+            .filter(|category| matches!(*category, "image" | "video" | "audio"))
+            .unwrap_or("other");
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            content_type: content_type.to_string(),
+        }
     }
 }
 
-/// [`BotLabels`] are the [labels](EncodeLabelSet) for the `bot` metric.
+/// [`MessageMentionsLabels`] are the [labels](EncodeLabelSet) for the `message_mentions` metric.
 ///
-/// This metric is not included in the member metric (using a label) as the user bot status has to
-/// be explicitly requested on guild creation. As such, they are separated to ensure that the member
-/// metric does not suffer from additional requests (that could potentially fail).
+/// The mention type is either `user`, `role` or `everyone`.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
-pub struct BotLabels {}
+pub struct MessageMentionsLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub mention_type: String,
+}
 
-impl BotLabels {
-    /// Creates a new instance of [`BotLabels`].
-    pub fn new() -> Self {
-        Self {}
+impl MessageMentionsLabels {
+    /// Creates a new instance of [`MessageMentionsLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, mention_type: &str) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            mention_type: mention_type.to_string(),
+        }
     }
 }
 
-/// [`MemberStatusLabels`] are the [labels](EncodeLabelSet) for the `member_status` metric.
+/// [`MessageEditedLabels`] are the [labels](EncodeLabelSet) for the `message_edited` metric.
+#[allow(clippy::struct_field_names)]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
-pub struct MemberStatusLabels {
-    pub status: String,
+pub struct MessageEditedLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
 }
 
-impl MemberStatusLabels {
-    /// Creates a new instance of [`MemberStatusLabels`].
-    pub fn new(status: OnlineStatus) -> Self {
+impl MessageEditedLabels {
+    /// Creates a new instance of [`MessageEditedLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId) -> Self {
         Self {
-            status: status.name().to_string(),
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
         }
     }
 }
 
-/// [`MemberVoiceLabels`] are the [labels](EncodeLabelSet) for the `member_voice` metric.
+/// [`MessageDeletedLabels`] are the [labels](EncodeLabelSet) for the `message_deleted` metric.
+///
+/// Messages that were deleted at once (e.g. by purges) are labelled as `bulk`.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
-pub struct MemberVoiceLabels {
+pub struct MessageDeletedLabels {
     pub category_id: Option<u64>,
     pub channel_id: u64,
-    pub self_stream: Boolean,
-    pub self_video: Boolean,
-    pub self_deaf: Boolean,
-    pub self_mute: Boolean,
+    pub bulk: Boolean,
 }
 
-impl MemberVoiceLabels {
-    /// Creates a new instance of [`MemberVoiceLabels`].
-    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, voice: &VoiceState) -> Self {
+impl MessageDeletedLabels {
+    /// Creates a new instance of [`MessageDeletedLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, bulk: bool) -> Self {
         Self {
             category_id: category_id.map(ChannelId::get),
             channel_id: channel_id.get(),
-            self_stream: voice.self_stream.unwrap_or(false).into(),
-            self_video: voice.self_video.into(),
-            self_deaf: voice.self_deaf.into(),
-            self_mute: voice.self_mute.into(),
+            bulk: Boolean(bulk),
         }
     }
 }
 
-/// [`MessageSentLabels`] are the [labels](EncodeLabelSet) for the `message_sent` metric.
-#[allow(clippy::struct_field_names)]
+/// [`MessageMatchedLabels`] are the [labels](EncodeLabelSet) for the `message_matched` metric.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
-pub struct MessageSentLabels {
+pub struct MessageMatchedLabels {
+    pub pattern: String,
     pub category_id: Option<u64>,
     pub channel_id: u64,
 }
 
-impl MessageSentLabels {
-    /// Creates a new instance of [`MessageSentLabels`].
-    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId) -> Self {
+impl MessageMatchedLabels {
+    /// Creates a new instance of [`MessageMatchedLabels`].
+    pub fn new(pattern: String, category_id: Option<ChannelId>, channel_id: ChannelId) -> Self {
         Self {
+            pattern,
             category_id: category_id.map(ChannelId::get),
             channel_id: channel_id.get(),
         }
@@ -185,12 +996,15 @@ impl MessageSentLabels {
 }
 
 /// [`EmoteUsedLabels`] are the [labels](EncodeLabelSet) for the `emote_used` metric.
+///
+/// Custom emojis are identified by their id, Unicode emojis only by their normalized name.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 pub struct EmoteUsedLabels {
     pub category_id: Option<u64>,
     pub channel_id: u64,
     pub reaction: Boolean,
-    pub emoji_id: u64,
+    pub custom: Boolean,
+    pub emoji_id: Option<u64>,
     pub emoji_name: Option<String>,
 }
 
@@ -200,32 +1014,108 @@ impl EmoteUsedLabels {
         category_id: Option<ChannelId>,
         channel_id: ChannelId,
         reaction: bool,
-        emoji_id: EmojiId,
-        emoji_name: Option<String>,
+        emoji: &ReactionEmoji,
     ) -> Self {
         Self {
             category_id: category_id.map(ChannelId::get),
             channel_id: channel_id.get(),
             reaction: Boolean(reaction),
-            emoji_id: emoji_id.get(),
-            emoji_name,
+            custom: Boolean(emoji.id().is_some()),
+            emoji_id: emoji.id().map(EmojiId::get),
+            emoji_name: emoji.name(),
+        }
+    }
+}
+
+/// [`EmoteRemovedLabels`] are the [labels](EncodeLabelSet) for the `emote_removed` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct EmoteRemovedLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub custom: Boolean,
+    pub emoji_id: Option<u64>,
+    pub emoji_name: Option<String>,
+}
+
+impl EmoteRemovedLabels {
+    /// Creates a new instance of [`EmoteRemovedLabels`].
+    pub fn new(
+        category_id: Option<ChannelId>,
+        channel_id: ChannelId,
+        emoji: &ReactionEmoji,
+    ) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            custom: Boolean(emoji.id().is_some()),
+            emoji_id: emoji.id().map(EmojiId::get),
+            emoji_name: emoji.name(),
+        }
+    }
+}
+
+/// [`ReactionClearedLabels`] are the [labels](EncodeLabelSet) for the `reaction_cleared` metric.
+///
+/// Reactions are either cleared for all emojis of a message at once or for a single emoji.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ReactionClearedLabels {
+    pub category_id: Option<u64>,
+    pub channel_id: u64,
+    pub all: Boolean,
+}
+
+impl ReactionClearedLabels {
+    /// Creates a new instance of [`ReactionClearedLabels`].
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, all: bool) -> Self {
+        Self {
+            category_id: category_id.map(ChannelId::get),
+            channel_id: channel_id.get(),
+            all: Boolean(all),
+        }
+    }
+}
+
+/// [`ReactionActiveLabels`] are the [labels](EncodeLabelSet) for the `reaction_active` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ReactionActiveLabels {
+    pub custom: Boolean,
+    pub emoji_id: Option<u64>,
+    pub emoji_name: Option<String>,
+}
+
+impl ReactionActiveLabels {
+    /// Creates a new instance of [`ReactionActiveLabels`].
+    pub fn new(emoji: &ReactionEmoji) -> Self {
+        Self {
+            custom: Boolean(emoji.id().is_some()),
+            emoji_id: emoji.id().map(EmojiId::get),
+            emoji_name: emoji.name(),
         }
     }
 }
 
 /// [`ActivityLabels`] are the [labels](EncodeLabelSet) for the `activity` metric.
+///
+/// Activity names are merged through the aliases (activity name to alias), if present. Merged activities are not
+/// labeled with an application id, as the merged activities may belong to different applications.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 pub struct ActivityLabels {
     pub activity_application_id: Option<u64>,
     pub activity_name: String,
+    pub activity_type: String,
 }
 
 impl ActivityLabels {
     /// Creates a new instance of [`ActivityLabels`].
-    pub fn new(activity: &Activity) -> Self {
+    pub fn new(activity: &Activity, aliases: &HashMap<String, String>) -> Self {
+        let alias = aliases.get(&activity.name);
         Self {
-            activity_application_id: activity.application_id.map(ApplicationId::get),
-            activity_name: activity.name.clone(),
+            activity_application_id: activity
+                .application_id
+                .filter(|_| alias.is_none())
+                .map(ApplicationId::get),
+            activity_name: alias.unwrap_or(&activity.name).clone(),
+            activity_type: activity_type(activity.kind).to_string(),
         }
     }
 }
@@ -235,22 +1125,64 @@ pub struct Handler {
     registry: Registry,
     pub guild: Family<GuildsLabels, Gauge>,
     pub channel: Family<ChannelLabels, Gauge>,
+    pub role: Family<RoleLabels, Gauge>,
     pub boost: Family<BoostLabels, Gauge>,
     pub member: Family<MemberLabels, Gauge>,
+    pub member_joined: Family<MemberJoinedLabels, Counter>,
+    pub member_left: Family<MemberLeftLabels, Counter>,
+    pub member_tenure: Family<MemberTenureLabels, Histogram, HistogramConstructor>,
+    pub member_joined_young: Family<MemberJoinedYoungLabels, Counter>,
+    pub raid_suspected: Family<RaidSuspectedLabels, Gauge>,
+    pub member_banned: Family<MemberBannedLabels, Counter>,
+    pub member_unbanned: Family<MemberUnbannedLabels, Counter>,
+    pub member_kicked: Family<MemberKickedLabels, Counter>,
+    pub member_timeout: Family<MemberTimeoutLabels, Counter>,
+    pub member_timed_out: Family<MemberTimedOutLabels, Gauge>,
+    pub moderator_action: Family<ModeratorActionLabels, Counter>,
+    pub audit_log_entry: Family<AuditLogEntryLabels, Counter>,
+    pub automod_action: Family<AutomodActionLabels, Counter>,
+    pub automod_rule: Family<AutomodRuleLabels, Gauge>,
     pub bot: Family<BotLabels, Gauge>,
     pub member_status: Family<MemberStatusLabels, Gauge>,
+    pub member_client_status: Family<MemberClientStatusLabels, Gauge>,
+    pub status_transition: Family<StatusTransitionLabels, Counter>,
+    pub member_online_peak: Family<MemberOnlinePeakLabels, Gauge>,
+    pub active_users: Family<ActiveUsersLabels, Gauge>,
+    pub member_inactive: Family<MemberInactiveLabels, Gauge>,
     pub member_voice: Family<MemberVoiceLabels, Gauge>,
+    pub voice_seconds: Family<VoiceSecondsLabels, Counter<f64, AtomicU64>>,
+    pub voice_session_duration_seconds:
+        Family<VoiceSessionDurationLabels, Histogram, HistogramConstructor>,
+    pub stage_instance: Family<StageInstanceLabels, Gauge>,
+    pub voice_channel_capacity: Family<VoiceChannelCapacityLabels, Gauge>,
+    pub role_member: Family<RoleMemberLabels, Gauge>,
     pub message_sent: Family<MessageSentLabels, Counter>,
+    pub message_kind: Family<MessageKindLabels, Counter>,
+    pub bot_message_sent: Family<BotMessageSentLabels, Counter>,
+    pub message_length: Family<MessageShapeLabels, Histogram, HistogramConstructor>,
+    pub message_attachments: Family<MessageShapeLabels, Histogram, HistogramConstructor>,
+    pub message_attachment_type: Family<MessageAttachmentTypeLabels, Counter>,
+    pub message_embeds: Family<MessageShapeLabels, Histogram, HistogramConstructor>,
+    pub message_mentions: Family<MessageMentionsLabels, Counter>,
+    pub message_reply: Family<MessageShapeLabels, Counter>,
+    pub message_edited: Family<MessageEditedLabels, Counter>,
+    pub message_deleted: Family<MessageDeletedLabels, Counter>,
+    pub message_matched: Family<MessageMatchedLabels, Counter>,
     pub emote_used: Family<EmoteUsedLabels, Counter>,
+    pub emote_removed: Family<EmoteRemovedLabels, Counter>,
+    pub reaction_cleared: Family<ReactionClearedLabels, Counter>,
+    pub reaction_active: Family<ReactionActiveLabels, Gauge>,
     pub activity: Family<ActivityLabels, Gauge>,
+    pub activity_seconds: Family<ActivityLabels, Counter<f64, AtomicU64>>,
 }
 
 impl Handler {
     /// Creates a new [Handler] metrics bundle with its own [Registry].
     ///
-    /// The [Registry] is created using a [PREFIX].
-    #[instrument]
-    pub fn new() -> Self {
+    /// The [Registry] is created using a [PREFIX]. The metrics of the [collectors](Collector) are registered in the same
+    /// [Registry].
+    #[instrument(skip(collectors))]
+    pub fn new(collectors: &[Arc<dyn Collector>]) -> Self {
         debug!(prefix = PREFIX, "Building metrics registry");
         let mut registry = <Registry>::with_prefix(PREFIX);
 
@@ -270,6 +1202,14 @@ impl Handler {
             channel.clone(),
         );
 
+        debug!(metrics_name = "role", "Building metric");
+        let role = Family::<RoleLabels, Gauge>::default();
+        registry.register(
+            "role",
+            "The number of roles on the guild. Used for injecting role names and colors into other metrics.",
+            role.clone(),
+        );
+
         debug!(metrics_name = "boost", "Building metric");
         let boost = Family::<BoostLabels, Gauge>::default();
         registry.register(
@@ -286,6 +1226,121 @@ impl Handler {
             member.clone(),
         );
 
+        debug!(metrics_name = "member_joined", "Building metric");
+        let member_joined = Family::<MemberJoinedLabels, Counter>::default();
+        registry.register(
+            "member_joined",
+            "The total number of members that joined the guild.",
+            member_joined.clone(),
+        );
+
+        debug!(metrics_name = "member_left", "Building metric");
+        let member_left = Family::<MemberLeftLabels, Counter>::default();
+        registry.register(
+            "member_left",
+            "The total number of members that left the guild.",
+            member_left.clone(),
+        );
+
+        debug!(metrics_name = "member_tenure", "Building metric");
+        let member_tenure =
+            Family::<MemberTenureLabels, Histogram, HistogramConstructor>::new_with_constructor(
+                || Histogram::new(MEMBER_TENURE_BUCKETS),
+            );
+        registry.register(
+            "member_tenure",
+            "The time (in seconds) members were on the guild when they left.",
+            member_tenure.clone(),
+        );
+
+        debug!(metrics_name = "member_joined_young", "Building metric");
+        let member_joined_young = Family::<MemberJoinedYoungLabels, Counter>::default();
+        registry.register(
+            "member_joined_young",
+            "The total number of members with recently created accounts that joined the guild.",
+            member_joined_young.clone(),
+        );
+
+        debug!(metrics_name = "raid_suspected", "Building metric");
+        let raid_suspected = Family::<RaidSuspectedLabels, Gauge>::default();
+        registry.register(
+            "raid_suspected",
+            "Whether a raid is suspected based on the recent member joins.",
+            raid_suspected.clone(),
+        );
+
+        debug!(metrics_name = "member_banned", "Building metric");
+        let member_banned = Family::<MemberBannedLabels, Counter>::default();
+        registry.register(
+            "member_banned",
+            "The total number of members that were banned from the guild.",
+            member_banned.clone(),
+        );
+
+        debug!(metrics_name = "member_unbanned", "Building metric");
+        let member_unbanned = Family::<MemberUnbannedLabels, Counter>::default();
+        registry.register(
+            "member_unbanned",
+            "The total number of users that were unbanned from the guild.",
+            member_unbanned.clone(),
+        );
+
+        debug!(metrics_name = "member_kicked", "Building metric");
+        let member_kicked = Family::<MemberKickedLabels, Counter>::default();
+        registry.register(
+            "member_kicked",
+            "The total number of members that were kicked from the guild per moderator role.",
+            member_kicked.clone(),
+        );
+
+        debug!(metrics_name = "member_timeout", "Building metric");
+        let member_timeout = Family::<MemberTimeoutLabels, Counter>::default();
+        registry.register(
+            "member_timeout",
+            "The total number of timeouts of members on the guild.",
+            member_timeout.clone(),
+        );
+
+        debug!(metrics_name = "member_timed_out", "Building metric");
+        let member_timed_out = Family::<MemberTimedOutLabels, Gauge>::default();
+        registry.register(
+            "member_timed_out",
+            "The number of members that are currently timed out on the guild.",
+            member_timed_out.clone(),
+        );
+
+        debug!(metrics_name = "moderator_action", "Building metric");
+        let moderator_action = Family::<ModeratorActionLabels, Counter>::default();
+        registry.register(
+            "moderator_action",
+            "The total number of bans, unbans, kicks and timeouts on the guild per moderator role.",
+            moderator_action.clone(),
+        );
+
+        debug!(metrics_name = "audit_log_entry", "Building metric");
+        let audit_log_entry = Family::<AuditLogEntryLabels, Counter>::default();
+        registry.register(
+            "audit_log_entry",
+            "The total number of audit log entries on the guild per action and user role.",
+            audit_log_entry.clone(),
+        );
+
+        debug!(metrics_name = "automod_action", "Building metric");
+        let automod_action = Family::<AutomodActionLabels, Counter>::default();
+        registry.register(
+            "automod_action",
+            "The total number of AutoMod actions executed on the guild per rule and action type.",
+            automod_action.clone(),
+        );
+
+        debug!(metrics_name = "automod_rule", "Building metric");
+        let automod_rule = Family::<AutomodRuleLabels, Gauge>::default();
+        registry.register(
+            "automod_rule",
+            "The AutoMod rules configured on the guild.",
+            automod_rule.clone(),
+        );
+
         debug!(metrics_name = "bot", "Building metric");
         let bot = Family::<BotLabels, Gauge>::default();
         registry.register(
@@ -302,6 +1357,46 @@ impl Handler {
             member_status.clone(),
         );
 
+        debug!(metrics_name = "member_client_status", "Building metric");
+        let member_client_status = Family::<MemberClientStatusLabels, Gauge>::default();
+        registry.register(
+            "member_client_status",
+            "The number of members on the guild per client platform and status.",
+            member_client_status.clone(),
+        );
+
+        debug!(metrics_name = "status_transition", "Building metric");
+        let status_transition = Family::<StatusTransitionLabels, Counter>::default();
+        registry.register(
+            "status_transition",
+            "The total number of status changes of members on the guild.",
+            status_transition.clone(),
+        );
+
+        debug!(metrics_name = "member_online_peak", "Building metric");
+        let member_online_peak = Family::<MemberOnlinePeakLabels, Gauge>::default();
+        registry.register(
+            "member_online_peak",
+            "The peak number of online members on the guild per window.",
+            member_online_peak.clone(),
+        );
+
+        debug!(metrics_name = "active_users", "Building metric");
+        let active_users = Family::<ActiveUsersLabels, Gauge>::default();
+        registry.register(
+            "active_users",
+            "The estimated number of unique active members per window.",
+            active_users.clone(),
+        );
+
+        debug!(metrics_name = "member_inactive", "Building metric");
+        let member_inactive = Family::<MemberInactiveLabels, Gauge>::default();
+        registry.register(
+            "member_inactive",
+            "The number of members that were inactive for at least the number of days.",
+            member_inactive.clone(),
+        );
+
         debug!(metrics_name = "member_voice", "Building metric");
         let member_voice = Family::<MemberVoiceLabels, Gauge>::default();
         registry.register(
@@ -310,6 +1405,55 @@ impl Handler {
             member_voice.clone(),
         );
 
+        debug!(metrics_name = "voice_seconds", "Building metric");
+        let voice_seconds = Family::<VoiceSecondsLabels, Counter<f64, AtomicU64>>::default();
+        registry.register(
+            "voice_seconds",
+            "The total time (in seconds) members spent in voice channels.",
+            voice_seconds.clone(),
+        );
+
+        debug!(
+            metrics_name = "voice_session_duration_seconds",
+            "Building metric"
+        );
+        let voice_session_duration_seconds = Family::<
+            VoiceSessionDurationLabels,
+            Histogram,
+            HistogramConstructor,
+        >::new_with_constructor(|| {
+            Histogram::new(VOICE_SESSION_DURATION_BUCKETS)
+        });
+        registry.register(
+            "voice_session_duration_seconds",
+            "The duration (in seconds) of voice sessions of members in a single voice channel.",
+            voice_session_duration_seconds.clone(),
+        );
+
+        debug!(metrics_name = "stage_instance", "Building metric");
+        let stage_instance = Family::<StageInstanceLabels, Gauge>::default();
+        registry.register(
+            "stage_instance",
+            "The number of live stage instances on the guild.",
+            stage_instance.clone(),
+        );
+
+        debug!(metrics_name = "voice_channel_capacity", "Building metric");
+        let voice_channel_capacity = Family::<VoiceChannelCapacityLabels, Gauge>::default();
+        registry.register(
+            "voice_channel_capacity",
+            "The user limit of voice channels on the guild (0 if unlimited).",
+            voice_channel_capacity.clone(),
+        );
+
+        debug!(metrics_name = "role_member", "Building metric");
+        let role_member = Family::<RoleMemberLabels, Gauge>::default();
+        registry.register(
+            "role_member",
+            "The number of members on the guild per role.",
+            role_member.clone(),
+        );
+
         debug!(metrics_name = "message_sent", "Building metric");
         let message_sent = Family::<MessageSentLabels, Counter>::default();
         registry.register(
@@ -318,6 +1462,103 @@ impl Handler {
             message_sent.clone(),
         );
 
+        debug!(metrics_name = "message_kind", "Building metric");
+        let message_kind = Family::<MessageKindLabels, Counter>::default();
+        registry.register(
+            "message_kind",
+            "The total number of discord messages sent on the guild per message type.",
+            message_kind.clone(),
+        );
+
+        debug!(metrics_name = "bot_message_sent", "Building metric");
+        let bot_message_sent = Family::<BotMessageSentLabels, Counter>::default();
+        registry.register(
+            "bot_message_sent",
+            "The total number of discord messages sent by allowed bots and webhooks.",
+            bot_message_sent.clone(),
+        );
+
+        debug!(metrics_name = "message_length", "Building metric");
+        let message_length =
+            Family::<MessageShapeLabels, Histogram, HistogramConstructor>::new_with_constructor(
+                || Histogram::new(MESSAGE_LENGTH_BUCKETS),
+            );
+        registry.register(
+            "message_length",
+            "The content length (in characters) of discord messages sent by guild members.",
+            message_length.clone(),
+        );
+
+        debug!(metrics_name = "message_attachments", "Building metric");
+        let message_attachments =
+            Family::<MessageShapeLabels, Histogram, HistogramConstructor>::new_with_constructor(
+                || Histogram::new(MESSAGE_COUNT_BUCKETS),
+            );
+        registry.register(
+            "message_attachments",
+            "The number of attachments of discord messages sent by guild members.",
+            message_attachments.clone(),
+        );
+
+        debug!(metrics_name = "message_attachment_type", "Building metric");
+        let message_attachment_type = Family::<MessageAttachmentTypeLabels, Counter>::default();
+        registry.register(
+            "message_attachment_type",
+            "The total number of attachments in discord messages sent by guild members per content type.",
+            message_attachment_type.clone(),
+        );
+
+        debug!(metrics_name = "message_embeds", "Building metric");
+        let message_embeds =
+            Family::<MessageShapeLabels, Histogram, HistogramConstructor>::new_with_constructor(
+                || Histogram::new(MESSAGE_COUNT_BUCKETS),
+            );
+        registry.register(
+            "message_embeds",
+            "The number of embeds of discord messages sent by guild members.",
+            message_embeds.clone(),
+        );
+
+        debug!(metrics_name = "message_mentions", "Building metric");
+        let message_mentions = Family::<MessageMentionsLabels, Counter>::default();
+        registry.register(
+            "message_mentions",
+            "The total number of mentions in discord messages sent by guild members.",
+            message_mentions.clone(),
+        );
+
+        debug!(metrics_name = "message_reply", "Building metric");
+        let message_reply = Family::<MessageShapeLabels, Counter>::default();
+        registry.register(
+            "message_reply",
+            "The total number of discord messages sent by guild members that are replies.",
+            message_reply.clone(),
+        );
+
+        debug!(metrics_name = "message_edited", "Building metric");
+        let message_edited = Family::<MessageEditedLabels, Counter>::default();
+        registry.register(
+            "message_edited",
+            "The total number of discord messages edited by guild members.",
+            message_edited.clone(),
+        );
+
+        debug!(metrics_name = "message_deleted", "Building metric");
+        let message_deleted = Family::<MessageDeletedLabels, Counter>::default();
+        registry.register(
+            "message_deleted",
+            "The total number of discord messages deleted on the guild.",
+            message_deleted.clone(),
+        );
+
+        debug!(metrics_name = "message_matched", "Building metric");
+        let message_matched = Family::<MessageMatchedLabels, Counter>::default();
+        registry.register(
+            "message_matched",
+            "The total number of discord messages sent by guild members that matched a pattern.",
+            message_matched.clone(),
+        );
+
         debug!(metrics_name = "emote_used", "Building metric");
         let emote_used = Family::<EmoteUsedLabels, Counter>::default();
         registry.register(
@@ -326,6 +1567,30 @@ impl Handler {
             emote_used.clone(),
         );
 
+        debug!(metrics_name = "emote_removed", "Building metric");
+        let emote_removed = Family::<EmoteRemovedLabels, Counter>::default();
+        registry.register(
+            "emote_removed",
+            "The total number of discord emote reactions removed by guild members.",
+            emote_removed.clone(),
+        );
+
+        debug!(metrics_name = "reaction_cleared", "Building metric");
+        let reaction_cleared = Family::<ReactionClearedLabels, Counter>::default();
+        registry.register(
+            "reaction_cleared",
+            "The total number of discord reaction clears on messages.",
+            reaction_cleared.clone(),
+        );
+
+        debug!(metrics_name = "reaction_active", "Building metric");
+        let reaction_active = Family::<ReactionActiveLabels, Gauge>::default();
+        registry.register(
+            "reaction_active",
+            "The number of active reactions on recently reacted messages.",
+            reaction_active.clone(),
+        );
+
         debug!(metrics_name = "activity", "Building metric");
         let activity = Family::<ActivityLabels, Gauge>::default();
         registry.register(
@@ -334,50 +1599,156 @@ impl Handler {
             activity.clone(),
         );
 
+        debug!(metrics_name = "activity_seconds", "Building metric");
+        let activity_seconds = Family::<ActivityLabels, Counter<f64, AtomicU64>>::default();
+        registry.register(
+            "activity_seconds",
+            "The total time (in seconds) members spent in activities.",
+            activity_seconds.clone(),
+        );
+
+        for collector in collectors {
+            debug!("Registering collector metrics");
+            collector.register(&mut registry);
+        }
+
         Self {
             registry,
             // metrics
             guild,
             channel,
+            role,
             boost,
             member,
+            member_joined,
+            member_left,
+            member_tenure,
+            member_joined_young,
+            raid_suspected,
+            member_banned,
+            member_unbanned,
+            member_kicked,
+            member_timeout,
+            member_timed_out,
+            moderator_action,
+            audit_log_entry,
+            automod_action,
+            automod_rule,
             bot,
             member_status,
+            member_client_status,
+            status_transition,
+            member_online_peak,
+            active_users,
+            member_inactive,
             member_voice,
+            voice_seconds,
+            voice_session_duration_seconds,
+            stage_instance,
+            voice_channel_capacity,
+            role_member,
             message_sent,
+            message_kind,
+            bot_message_sent,
+            message_length,
+            message_attachments,
+            message_attachment_type,
+            message_embeds,
+            message_mentions,
+            message_reply,
+            message_edited,
+            message_deleted,
+            message_matched,
             emote_used,
+            emote_removed,
+            reaction_cleared,
+            reaction_active,
             activity,
+            activity_seconds,
         }
     }
 
     pub fn clear(&self) {
         self.guild.clear();
         self.channel.clear();
+        self.role.clear();
         self.boost.clear();
         self.member.clear();
+        self.member_joined.clear();
+        self.member_left.clear();
+        self.member_tenure.clear();
+        self.member_joined_young.clear();
+        self.raid_suspected.clear();
+        self.member_banned.clear();
+        self.member_unbanned.clear();
+        self.member_kicked.clear();
+        self.member_timeout.clear();
+        self.member_timed_out.clear();
+        self.moderator_action.clear();
+        self.audit_log_entry.clear();
+        self.automod_action.clear();
+        self.automod_rule.clear();
         self.bot.clear();
         self.member_status.clear();
+        self.member_client_status.clear();
+        self.status_transition.clear();
+        self.member_online_peak.clear();
+        self.active_users.clear();
+        self.member_inactive.clear();
         self.member_voice.clear();
+        self.voice_seconds.clear();
+        self.voice_session_duration_seconds.clear();
+        self.stage_instance.clear();
+        self.voice_channel_capacity.clear();
+        self.role_member.clear();
         self.message_sent.clear();
+        self.message_kind.clear();
+        self.bot_message_sent.clear();
+        self.message_length.clear();
+        self.message_attachments.clear();
+        self.message_attachment_type.clear();
+        self.message_embeds.clear();
+        self.message_mentions.clear();
+        self.message_reply.clear();
+        self.message_edited.clear();
+        self.message_deleted.clear();
+        self.message_matched.clear();
         self.emote_used.clear();
+        self.emote_removed.clear();
+        self.reaction_cleared.clear();
+        self.reaction_active.clear();
         self.activity.clear();
+        self.activity_seconds.clear();
     }
 }
 
 /// Serves a shared [Handler] using a [webserver](Router).
 ///
 /// Use the [CancellationToken] to cancel and gracefully shutdown the [Handler].
-/// The metrics can be accessed using the `/metrics` path. It doesn't enforce any authentication.
-#[instrument(skip(handler, shutdown))]
+/// The metrics can be accessed using the `/metrics` path. If the [`LastSeen`] store and an inactive token are present,
+/// the inactive members can be listed using the `/inactive/{days}` path. It requires the token as a bearer token.
+#[instrument(skip(handler, last_seen, inactive_token, shutdown))]
 pub async fn serve(
     address: &SocketAddr,
     handler: Arc<Handler>,
+    last_seen: Option<Arc<LastSeen>>,
+    inactive_token: Option<String>,
     shutdown: CancellationToken,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Create webserver for metrics
-    let rest_app = Router::new()
+    let mut rest_app = Router::new()
         .route("/", get(index))
-        .route("/metrics", get(metrics))
+        .route("/metrics", get(metrics));
+
+    // Add inactive members endpoint (only if last seen tracking and its token are enabled)
+    if let Some((last_seen, inactive_token)) = last_seen.zip(inactive_token) {
+        rest_app = rest_app
+            .route("/inactive/{days}", get(inactive))
+            .layer(Extension(last_seen))
+            .layer(Extension(InactiveToken(Arc::from(inactive_token))));
+    }
+
+    let rest_app = rest_app
         .layer(Extension(Arc::clone(&handler)))
         .layer(TraceLayer::new_for_http())
         .with_state(());
@@ -423,3 +1794,87 @@ async fn metrics(Extension(handler): Extension<Arc<Handler>>) -> Response {
         .body(Body::from(buffer))
         .expect("failed to build response")
 }
+
+/// [`InactiveToken`] is the bearer token that is required for the inactive members endpoint.
+#[derive(Clone)]
+struct InactiveToken(Arc<str>);
+
+impl InactiveToken {
+    /// Checks whether the `Authorization` header carries the token. The token is compared in constant time.
+    fn authorizes(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+        token.len() == self.0.len()
+            && token
+                .bytes()
+                .zip(self.0.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+/// The inactive members endpoint handler. It lists the ids of all members that were inactive for at least the number
+/// of days, one per line. Requests without the [bearer token](InactiveToken) are rejected.
+#[instrument(skip(last_seen, token, headers))]
+async fn inactive(
+    Extension(last_seen): Extension<Arc<LastSeen>>,
+    Extension(token): Extension<InactiveToken>,
+    headers: HeaderMap,
+    Path(days): Path<u64>,
+) -> Response {
+    debug!("Handling inactive members request");
+
+    // Reject unauthorized requests
+    if !token.authorizes(&headers) {
+        return Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .header(WWW_AUTHENTICATE, "Bearer")
+            .body(Body::empty())
+            .expect("failed to build response");
+    }
+
+    // Encode the member ids into the buffer
+    let buffer: String = last_seen
+        .inactive(days, now_millis() / 1000)
+        .await
+        .iter()
+        .map(|user_id| format!("{user_id}\n"))
+        .collect();
+
+    // Respond with member ids
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(buffer))
+        .expect("failed to build response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(authorization).expect("valid header"),
+        );
+        headers
+    }
+
+    #[test]
+    fn inactive_token_authorizes_bearer_token() {
+        let token = InactiveToken(Arc::from("secret"));
+        assert!(token.authorizes(&headers("Bearer secret")));
+        assert!(!token.authorizes(&headers("Bearer secrets")));
+        assert!(!token.authorizes(&headers("Bearer other!")));
+        assert!(!token.authorizes(&headers("Basic secret")));
+        assert!(!token.authorizes(&HeaderMap::new()));
+    }
+}
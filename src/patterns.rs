@@ -0,0 +1,244 @@
+//! This module implements the named message content patterns. The patterns are loaded from a file and reloaded
+//! whenever the file changes.
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, instrument, warn};
+
+/// The interval in which the patterns file is checked for changes.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// [`PatternsFile`] is the file representation of the [`Patterns`].
+#[derive(Debug, Deserialize)]
+struct PatternsFile {
+    #[serde(default, rename = "pattern")]
+    patterns: Vec<PatternEntry>,
+}
+
+/// [`PatternEntry`] is the file representation of a single [`Pattern`]. It either uses a regex or a list of keywords.
+#[derive(Debug, Deserialize)]
+struct PatternEntry {
+    name: String,
+    regex: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+}
+
+/// [`Pattern`] is a named matcher for message contents.
+#[derive(Debug)]
+pub struct Pattern {
+    name: String,
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Creates a new [`Pattern`] from its file representation. Keywords are matched as whole words, ignoring case.
+    fn new(entry: PatternEntry) -> Result<Self, Box<dyn std::error::Error>> {
+        let regex = match (entry.regex, entry.keywords.is_empty()) {
+            (Some(regex), true) => Regex::new(&regex)?,
+            (None, false) => {
+                let keywords: Vec<String> =
+                    entry.keywords.iter().map(|k| regex::escape(k)).collect();
+                RegexBuilder::new(&format!(r"\b(?:{})\b", keywords.join("|")))
+                    .case_insensitive(true)
+                    .build()?
+            }
+            _ => {
+                return Err(
+                    format!("pattern {} requires either a regex or keywords", entry.name).into(),
+                );
+            }
+        };
+        Ok(Self {
+            name: entry.name,
+            regex,
+        })
+    }
+
+    /// Checks whether the message content matches the pattern.
+    fn is_match(&self, content: &str) -> bool {
+        self.regex.is_match(content)
+    }
+}
+
+/// [`Patterns`] are the named message content patterns of a patterns file.
+pub struct Patterns {
+    path: PathBuf,
+    modified: RwLock<Option<SystemTime>>,
+    patterns: RwLock<Vec<Pattern>>,
+}
+
+impl Patterns {
+    /// Loads the [`Patterns`] from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be read or any of its patterns is invalid.
+    #[instrument]
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let patterns = parse(&std::fs::read_to_string(path)?)?;
+        info!(patterns = patterns.len(), "Loaded message patterns");
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            modified: RwLock::new(modified),
+            patterns: RwLock::new(patterns),
+        })
+    }
+
+    /// Gets the names of all patterns that match the message content.
+    pub async fn matches(&self, content: &str) -> Vec<String> {
+        self.patterns
+            .read()
+            .await
+            .iter()
+            .filter(|pattern| pattern.is_match(content))
+            .map(|pattern| pattern.name.clone())
+            .collect()
+    }
+
+    /// Watches the patterns file for changes and reloads the patterns. If the file is invalid, the previous patterns
+    /// are kept.
+    ///
+    /// Use the [CancellationToken] to stop watching the file.
+    #[instrument(skip(self, shutdown))]
+    pub async fn watch(&self, shutdown: CancellationToken) {
+        let mut interval = interval(RELOAD_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.reload().await,
+                () = shutdown.cancelled() => break,
+            }
+        }
+    }
+
+    /// Reloads the patterns if the patterns file was modified.
+    async fn reload(&self) {
+        let modified = match tokio::fs::metadata(&self.path)
+            .await
+            .and_then(|meta| meta.modified())
+        {
+            Ok(modified) => Some(modified),
+            Err(why) => {
+                warn!(err = %why, "Failed to check message patterns file");
+                return;
+            }
+        };
+        if *self.modified.read().await == modified {
+            debug!("Message patterns unchanged");
+            return;
+        }
+        *self.modified.write().await = modified;
+
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(why) => {
+                warn!(err = %why, "Failed to read message patterns file");
+                return;
+            }
+        };
+        let patterns = match parse(&content) {
+            Ok(patterns) => patterns,
+            Err(why) => {
+                warn!(
+                    err = why,
+                    "Failed to reload message patterns, keeping previous patterns"
+                );
+                return;
+            }
+        };
+        info!(patterns = patterns.len(), "Reloaded message patterns");
+        *self.patterns.write().await = patterns;
+    }
+}
+
+/// Parses and compiles all patterns of a patterns file.
+fn parse(content: &str) -> Result<Vec<Pattern>, Box<dyn std::error::Error>> {
+    let file: PatternsFile = toml::from_str(content)?;
+    file.patterns.into_iter().map(Pattern::new).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(patterns: &[Pattern], content: &str) -> Vec<String> {
+        patterns
+            .iter()
+            .filter(|pattern| pattern.is_match(content))
+            .map(|pattern| pattern.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn keywords_are_escaped() {
+        let patterns = parse(
+            r#"
+            [[pattern]]
+            name = "version"
+            keywords = ["v1.0"]
+            "#,
+        )
+        .expect("expected to parse");
+        assert_eq!(names(&patterns, "released v1.0 today"), vec!["version"]);
+        assert!(names(&patterns, "released v1x0 today").is_empty());
+    }
+
+    #[test]
+    fn keywords_are_word_bounded() {
+        let patterns = parse(
+            r#"
+            [[pattern]]
+            name = "bug"
+            keywords = ["bug", "crash"]
+            "#,
+        )
+        .expect("expected to parse");
+        assert_eq!(names(&patterns, "found a bug."), vec!["bug"]);
+        assert_eq!(names(&patterns, "crash on start"), vec!["bug"]);
+        assert!(names(&patterns, "debugging the crashes").is_empty());
+    }
+
+    #[test]
+    fn keywords_ignore_case() {
+        let patterns = parse(
+            r#"
+            [[pattern]]
+            name = "bug"
+            keywords = ["Bug"]
+            "#,
+        )
+        .expect("expected to parse");
+        assert_eq!(names(&patterns, "BUG report"), vec!["bug"]);
+        assert_eq!(names(&patterns, "bug report"), vec!["bug"]);
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        assert!(parse("[[pattern]]\nname = \"none\"").is_err());
+        assert!(parse("[[pattern]]\nname = \"both\"\nregex = \"a\"\nkeywords = [\"b\"]").is_err());
+        assert!(parse("[[pattern]]\nname = \"invalid\"\nregex = \"(\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn reload_keeps_previous_patterns_if_invalid() {
+        let path = std::env::temp_dir().join(format!("patterns-{}.toml", std::process::id()));
+        std::fs::write(&path, "[[pattern]]\nname = \"bug\"\nkeywords = [\"bug\"]\n")
+            .expect("expected to write");
+        let patterns = Patterns::load(&path).expect("expected to load");
+
+        std::fs::write(&path, "[[pattern]]\nname = \"invalid\"\nregex = \"(\"\n")
+            .expect("expected to write");
+        *patterns.modified.write().await = None;
+        patterns.reload().await;
+        std::fs::remove_file(&path).expect("expected to remove");
+
+        assert_eq!(patterns.matches("a bug").await, vec!["bug"]);
+    }
+}
@@ -0,0 +1,169 @@
+//! This module implements the peak tracking of online members. Peaks are tracked over rolling windows and since the
+//! start of the tracking.
+
+use serenity::all::UserId;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The rolling windows (and their names) that peaks are tracked over.
+const WINDOWS: [(&str, Duration); 2] = [
+    ("1h", Duration::from_secs(60 * 60)),
+    ("24h", Duration::from_secs(24 * 60 * 60)),
+];
+
+/// [`PeakWindow`] is the maximum of all samples within a rolling window. Only the samples that may still become the
+/// maximum are kept (in descending order).
+#[derive(Debug)]
+struct PeakWindow {
+    length: Duration,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl PeakWindow {
+    /// Records a sample and gets the maximum of the window.
+    fn record(&mut self, now: Instant, value: u64) -> u64 {
+        while self.samples.back().is_some_and(|(_, last)| *last <= value) {
+            self.samples.pop_back();
+        }
+        self.samples.push_back((now, value));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > self.length)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.front().map_or(value, |(_, peak)| *peak)
+    }
+}
+
+/// [`OnlinePeaks`] tracks the online members and the peaks of their number. The online members are kept as a set, such
+/// that repeated or unknown status changes are not counted twice.
+#[derive(Debug)]
+pub struct OnlinePeaks {
+    online: HashSet<UserId>,
+    peak: u64,
+    windows: Vec<(&'static str, PeakWindow)>,
+}
+
+impl OnlinePeaks {
+    /// Creates a new [`OnlinePeaks`] without any online members.
+    pub fn new() -> Self {
+        Self {
+            online: HashSet::new(),
+            peak: 0,
+            windows: WINDOWS
+                .iter()
+                .map(|(name, length)| {
+                    (
+                        *name,
+                        PeakWindow {
+                            length: *length,
+                            samples: VecDeque::new(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Applies the status of a member and gets the peaks per window (including `start`).
+    pub fn update(
+        &mut self,
+        now: Instant,
+        user_id: UserId,
+        is_online: bool,
+    ) -> Vec<(&'static str, u64)> {
+        if is_online {
+            self.online.insert(user_id);
+        } else {
+            self.online.remove(&user_id);
+        }
+        self.sample(now)
+    }
+
+    /// Samples the current number of online members and gets the peaks per window (including `start`).
+    pub fn sample(&mut self, now: Instant) -> Vec<(&'static str, u64)> {
+        let online = self.online.len().try_into().unwrap_or(u64::MAX);
+        self.peak = self.peak.max(online);
+
+        let mut peaks: Vec<_> = self
+            .windows
+            .iter_mut()
+            .map(|(name, window)| (*name, window.record(now, online)))
+            .collect();
+        peaks.push(("start", self.peak));
+        peaks
+    }
+
+    /// Clears the number of online members and all peaks.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(length: Duration) -> PeakWindow {
+        PeakWindow {
+            length,
+            samples: VecDeque::new(),
+        }
+    }
+
+    #[test]
+    fn window_keeps_maximum() {
+        let start = Instant::now();
+        let mut window = window(Duration::from_secs(10));
+        assert_eq!(window.record(start, 3), 3);
+        assert_eq!(window.record(start + Duration::from_secs(1), 5), 5);
+        assert_eq!(window.record(start + Duration::from_secs(2), 4), 5);
+        assert_eq!(window.record(start + Duration::from_secs(3), 1), 5);
+    }
+
+    #[test]
+    fn window_keeps_samples_descending() {
+        let start = Instant::now();
+        let mut window = window(Duration::from_secs(10));
+        for (offset, value) in [(0, 2), (1, 7), (2, 5), (3, 5), (4, 1), (5, 3)] {
+            window.record(start + Duration::from_secs(offset), value);
+        }
+        let values: Vec<u64> = window.samples.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, vec![7, 5, 3]);
+    }
+
+    #[test]
+    fn window_expires_samples() {
+        let start = Instant::now();
+        let mut window = window(Duration::from_secs(10));
+        window.record(start, 9);
+        window.record(start + Duration::from_secs(5), 4);
+        assert_eq!(window.record(start + Duration::from_secs(10), 2), 9);
+        assert_eq!(window.record(start + Duration::from_secs(11), 2), 4);
+        assert_eq!(window.record(start + Duration::from_secs(16), 1), 2);
+    }
+
+    #[test]
+    fn repeated_online_is_counted_once() {
+        let now = Instant::now();
+        let mut peaks = OnlinePeaks::new();
+        peaks.update(now, UserId::new(1), true);
+        let peaks = peaks.update(now, UserId::new(1), true);
+        assert!(peaks.iter().all(|(_, peak)| *peak == 1));
+    }
+
+    #[test]
+    fn peak_remains_after_offline() {
+        let now = Instant::now();
+        let mut peaks = OnlinePeaks::new();
+        peaks.update(now, UserId::new(1), true);
+        peaks.update(now, UserId::new(2), true);
+        peaks.update(now, UserId::new(1), false);
+        let peaks = peaks.update(now, UserId::new(3), false);
+        assert!(peaks.contains(&("start", 2)));
+        assert!(peaks.contains(&("1h", 2)));
+        assert_eq!(peaks.len(), WINDOWS.len() + 1);
+    }
+}
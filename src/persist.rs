@@ -0,0 +1,23 @@
+//! This module implements the persistence of the stores that survive restarts. Files are replaced atomically through
+//! a temporary file next to them, such that a crash while saving never leaves a truncated file behind.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+/// Writes the data to a file. The data is written to the file name with a `.tmp` suffix first and then renamed, such
+/// that stores with different files never share their temporary file.
+pub fn write(path: &Path, data: &[u8]) -> Result<(), Error> {
+    let mut temp = OsString::from(path.as_os_str());
+    temp.push(".tmp");
+    fs::write(&temp, data)?;
+    fs::rename(temp, path)
+}
+
+/// Writes the data to a file (see [`write`]) on the blocking thread pool, such that the async runtime is not blocked.
+pub async fn save(path: PathBuf, data: Vec<u8>) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || write(&path, &data))
+        .await
+        .unwrap_or_else(|why| Err(Error::other(why)))
+}
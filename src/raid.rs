@@ -0,0 +1,204 @@
+//! This module implements the join-spike raid detection. A raid is suspected whenever the number of member joins
+//! within a rolling window reaches a threshold.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// The maximum duration of posting a raid alert, such that a slow webhook cannot stall the event handling.
+pub const ALERT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// [`RaidDetector`] tracks the member joins of a rolling window.
+#[derive(Debug)]
+pub struct RaidDetector {
+    threshold: usize,
+    window: Duration,
+    joins: VecDeque<Instant>,
+    suspected: bool,
+}
+
+impl RaidDetector {
+    /// Creates a new [`RaidDetector`] that suspects a raid once `threshold` members joined within the `window`. The
+    /// detection is disabled if the threshold is zero.
+    pub fn new(threshold: usize, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            joins: VecDeque::new(),
+            suspected: false,
+        }
+    }
+
+    /// Checks whether the detection is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0
+    }
+
+    /// Gets the number of member joins within the current window.
+    pub fn joins(&self) -> usize {
+        self.joins.len()
+    }
+
+    /// Gets the length of the rolling window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Records a member join. Returns the new state if it changed.
+    pub fn record(&mut self, now: Instant) -> Option<bool> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.joins.push_back(now);
+        self.refresh(now)
+    }
+
+    /// Drops all member joins that left the window. Returns the new state if it changed.
+    pub fn refresh(&mut self, now: Instant) -> Option<bool> {
+        if !self.is_enabled() {
+            return None;
+        }
+        while let Some(join) = self.joins.front() {
+            if now.duration_since(*join) < self.window {
+                break;
+            }
+            self.joins.pop_front();
+        }
+
+        let suspected = self.joins.len() >= self.threshold;
+        if suspected == self.suspected {
+            return None;
+        }
+        self.suspected = suspected;
+        Some(suspected)
+    }
+
+    /// Clears all recorded member joins.
+    pub fn clear(&mut self) {
+        self.joins.clear();
+        self.suspected = false;
+    }
+}
+
+/// [`RaidAlert`] is the payload that is posted to the alert webhook whenever the raid state changes.
+#[derive(Debug, Serialize)]
+pub struct RaidAlert {
+    /// Whether a raid is suspected (`false` once the raid ended).
+    pub suspected: bool,
+    /// The number of member joins within the window.
+    pub joins: usize,
+    /// The length of the rolling window (in seconds).
+    pub window_seconds: u64,
+}
+
+/// Posts the [`RaidAlert`] to the alert webhook. Failures are only logged.
+pub async fn alert(client: &reqwest::Client, url: &str, alert: &RaidAlert) {
+    let result = client
+        .post(url)
+        .json(alert)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    match result {
+        Ok(_) => info!(suspected = alert.suspected, "Sent raid alert"),
+        Err(why) => warn!(err = %why, "Failed to send raid alert"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::routing::post;
+    use tokio::sync::mpsc;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn disabled_detector_never_changes_state() {
+        let mut detector = RaidDetector::new(0, WINDOW);
+        let now = Instant::now();
+        assert!(!detector.is_enabled());
+        assert_eq!(detector.record(now), None);
+        assert_eq!(detector.refresh(now), None);
+        assert_eq!(detector.joins(), 0);
+    }
+
+    #[test]
+    fn record_suspects_raid_at_threshold() {
+        let mut detector = RaidDetector::new(3, WINDOW);
+        let now = Instant::now();
+        assert_eq!(detector.record(now), None);
+        assert_eq!(detector.record(now + Duration::from_secs(1)), None);
+        assert_eq!(detector.record(now + Duration::from_secs(2)), Some(true));
+        // The state is only reported when it changes
+        assert_eq!(detector.record(now + Duration::from_secs(3)), None);
+        assert_eq!(detector.joins(), 4);
+    }
+
+    #[test]
+    fn refresh_expires_joins_and_ends_raid() {
+        let mut detector = RaidDetector::new(2, WINDOW);
+        let now = Instant::now();
+        detector.record(now);
+        assert_eq!(detector.record(now + Duration::from_secs(30)), Some(true));
+
+        // The first join leaves the window exactly after its length
+        assert_eq!(detector.refresh(now + Duration::from_secs(59)), None);
+        assert_eq!(detector.refresh(now + WINDOW), Some(false));
+        assert_eq!(detector.joins(), 1);
+        assert_eq!(detector.refresh(now + Duration::from_secs(90)), None);
+        assert_eq!(detector.joins(), 0);
+    }
+
+    #[test]
+    fn joins_outside_window_do_not_add_up() {
+        let mut detector = RaidDetector::new(2, WINDOW);
+        let now = Instant::now();
+        assert_eq!(detector.record(now), None);
+        assert_eq!(detector.record(now + WINDOW), None);
+        assert_eq!(detector.record(now + WINDOW * 2), None);
+        assert_eq!(detector.joins(), 1);
+    }
+
+    #[test]
+    fn clear_resets_state() {
+        let mut detector = RaidDetector::new(1, WINDOW);
+        let now = Instant::now();
+        assert_eq!(detector.record(now), Some(true));
+        detector.clear();
+        assert_eq!(detector.joins(), 0);
+        assert_eq!(detector.record(now), Some(true));
+    }
+
+    #[tokio::test]
+    async fn alert_posts_json_to_webhook() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/alert",
+            post(move |body: String| async move {
+                sender.send(body).expect("receiver dropped");
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind listener");
+        let address = listener.local_addr().expect("failed to get address");
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::builder()
+            .timeout(ALERT_TIMEOUT)
+            .build()
+            .expect("failed to build client");
+        let raid_alert = RaidAlert {
+            suspected: true,
+            joins: 25,
+            window_seconds: 60,
+        };
+        alert(&client, &format!("http://{address}/alert"), &raid_alert).await;
+
+        let body = receiver.recv().await.expect("no alert received");
+        assert_eq!(body, r#"{"suspected":true,"joins":25,"window_seconds":60}"#);
+    }
+}
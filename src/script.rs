@@ -0,0 +1,551 @@
+//! This module implements scripted custom metrics. The script is loaded once on startup, declares its metrics and
+//! is then evaluated on selected Discord gateway events.
+
+use crate::collector::Collector;
+use crate::discord::find_category_channel;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::Registry;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope};
+use serenity::all::{Context, FullEvent, Member, Message, Reaction, ReactionType};
+use serenity::async_trait;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, instrument, warn};
+
+/// The maximum number of operations a single script evaluation may perform.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// The maximum function call depth of a single script evaluation.
+const MAX_CALL_LEVELS: usize = 32;
+
+/// The maximum length of strings, arrays and maps created by the script.
+const MAX_SIZE: usize = 10_000;
+
+/// The maximum number of label sets (series) of a single scripted metric.
+const MAX_SERIES: usize = 1_000;
+
+/// The prefix (namespace) of all scripted metrics, such that they cannot collide with the built-in metrics.
+const PREFIX: &str = "script";
+
+/// [`ScriptLabels`] are the dynamic [labels](prometheus_client::encoding::EncodeLabelSet) of a scripted metric.
+type ScriptLabels = Vec<(String, String)>;
+
+/// [`ScriptMetric`] is a metric that was declared by the script.
+enum ScriptMetric {
+    Counter {
+        help: String,
+        labels: Vec<String>,
+        series: AtomicUsize,
+        family: Family<ScriptLabels, Counter>,
+    },
+    Gauge {
+        help: String,
+        labels: Vec<String>,
+        series: AtomicUsize,
+        family: Family<ScriptLabels, Gauge>,
+    },
+}
+
+impl ScriptMetric {
+    /// Creates the label set of the metric from a script map. Missing labels are encoded as empty values.
+    fn labels(&self, values: &Map) -> Result<ScriptLabels, Box<EvalAltResult>> {
+        let (Self::Counter { labels, .. } | Self::Gauge { labels, .. }) = self;
+        if let Some(key) = values
+            .keys()
+            .find(|key| !labels.iter().any(|l| l == key.as_str()))
+        {
+            return Err(format!("unknown label: {key}").into());
+        }
+        Ok(labels
+            .iter()
+            .map(|label| {
+                let value = values
+                    .get(label.as_str())
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                (label.clone(), value)
+            })
+            .collect())
+    }
+
+    /// Checks whether a label set may be updated. New label sets are only admitted until the metric reached
+    /// [`MAX_SERIES`], such that a script cannot grow the registry without bound.
+    fn admit(&self, name: &str, labels: &ScriptLabels) -> Result<(), Box<EvalAltResult>> {
+        let (exists, series) = match self {
+            Self::Counter { family, series, .. } => (family.get(labels).is_some(), series),
+            Self::Gauge { family, series, .. } => (family.get(labels).is_some(), series),
+        };
+        if exists {
+            return Ok(());
+        }
+        if series
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                (count < MAX_SERIES).then_some(count + 1)
+            })
+            .is_err()
+        {
+            return Err(format!("too many label sets (max {MAX_SERIES}): {name}").into());
+        }
+        Ok(())
+    }
+}
+
+/// [`ScriptMetrics`] are the metrics declared by the script, mapped by their name.
+#[derive(Default)]
+struct ScriptMetrics {
+    loaded: bool,
+    metrics: HashMap<String, ScriptMetric>,
+}
+
+/// [`ScriptCollector`] is a [`Collector`] that evaluates a [Rhai](rhai) script on Discord gateway events.
+///
+/// The script declares its metrics on load using `counter(name, help, labels)` and `gauge(name, help, labels)`. The
+/// metrics are exported with the `script` prefix (e.g. `dcexport_script_<name>`). The script then updates them from
+/// the functions `message`, `guild_member_addition` and `reaction_add` using `inc`, `inc_by`, `dec` and `set`. The
+/// script runs sandboxed, without access to modules, and with limited operations per event.
+pub struct ScriptCollector {
+    engine: Engine,
+    ast: AST,
+    metrics: Arc<RwLock<ScriptMetrics>>,
+}
+
+impl ScriptCollector {
+    /// Loads a script file and evaluates its declarations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script could not be compiled or its declarations failed to evaluate.
+    #[instrument]
+    pub fn load(path: &Path) -> Result<Self, Box<EvalAltResult>> {
+        let metrics = Arc::new(RwLock::new(ScriptMetrics::default()));
+        let engine = engine(&metrics);
+
+        debug!("Compiling script");
+        let ast = engine.compile_file(path.to_path_buf())?;
+
+        Self::declare(engine, ast, metrics)
+    }
+
+    /// Evaluates the declarations (top-level statements) of a compiled script.
+    fn declare(
+        engine: Engine,
+        ast: AST,
+        metrics: Arc<RwLock<ScriptMetrics>>,
+    ) -> Result<Self, Box<EvalAltResult>> {
+        debug!("Evaluating script declarations");
+        engine.run_ast_with_scope(&mut Scope::new(), &ast)?;
+
+        let mut declared = metrics.write().expect("script metrics poisoned");
+        declared.loaded = true;
+        info!(
+            metrics = declared.metrics.len(),
+            "Loaded script with custom metrics"
+        );
+        drop(declared);
+
+        Ok(Self {
+            engine,
+            ast,
+            metrics,
+        })
+    }
+
+    /// Invokes the script function for an event, if it is defined by the script.
+    fn call(&self, event: &str, data: Map) {
+        if !self.ast.iter_functions().any(|f| f.name == event) {
+            return;
+        }
+        // The declarations (top-level statements) are only evaluated once on load
+        let options = CallFnOptions::new().eval_ast(false);
+        if let Err(why) = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut Scope::new(),
+            &self.ast,
+            event,
+            (data,),
+        ) {
+            warn!(event, err = %why, "Failed to evaluate script");
+        }
+    }
+}
+
+/// Creates the sandboxed scripting [`Engine`] with the metrics API.
+fn engine(metrics: &Arc<RwLock<ScriptMetrics>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_SIZE)
+        .set_max_array_size(MAX_SIZE)
+        .set_max_map_size(MAX_SIZE)
+        .on_print(|text| info!(text, "Script print"))
+        .on_debug(|text, _, pos| debug!(text, pos = %pos, "Script debug"));
+
+    // Declarations
+    let declared = Arc::clone(metrics);
+    engine.register_fn(
+        "counter",
+        move |name: &str, help: &str, labels: Array| -> Result<(), Box<EvalAltResult>> {
+            declare(&declared, name, help, labels, true)
+        },
+    );
+    let declared = Arc::clone(metrics);
+    engine.register_fn(
+        "gauge",
+        move |name: &str, help: &str, labels: Array| -> Result<(), Box<EvalAltResult>> {
+            declare(&declared, name, help, labels, false)
+        },
+    );
+
+    // Updates
+    let declared = Arc::clone(metrics);
+    engine.register_fn(
+        "inc",
+        move |name: &str, labels: Map| -> Result<(), Box<EvalAltResult>> {
+            update(&declared, name, &labels, 1, Update::Add)
+        },
+    );
+    let declared = Arc::clone(metrics);
+    engine.register_fn(
+        "inc_by",
+        move |name: &str, labels: Map, value: i64| -> Result<(), Box<EvalAltResult>> {
+            update(&declared, name, &labels, value, Update::Add)
+        },
+    );
+    let declared = Arc::clone(metrics);
+    engine.register_fn(
+        "dec",
+        move |name: &str, labels: Map| -> Result<(), Box<EvalAltResult>> {
+            update(&declared, name, &labels, -1, Update::Add)
+        },
+    );
+    let declared = Arc::clone(metrics);
+    engine.register_fn(
+        "set",
+        move |name: &str, labels: Map, value: i64| -> Result<(), Box<EvalAltResult>> {
+            update(&declared, name, &labels, value, Update::Set)
+        },
+    );
+
+    engine
+}
+
+/// Declares a new metric. Metrics can only be declared while the script is loaded.
+fn declare(
+    metrics: &RwLock<ScriptMetrics>,
+    name: &str,
+    help: &str,
+    labels: Array,
+    counter: bool,
+) -> Result<(), Box<EvalAltResult>> {
+    let mut metrics = metrics.write().expect("script metrics poisoned");
+    if metrics.loaded {
+        return Err("metrics can only be declared when the script is loaded".into());
+    }
+    if metrics.metrics.contains_key(name) {
+        return Err(format!("metric already declared: {name}").into());
+    }
+    if !is_valid_name(name, true) {
+        return Err(format!("invalid metric name: {name}").into());
+    }
+
+    let labels: Vec<String> = labels.iter().map(ToString::to_string).collect();
+    if let Some(label) = labels
+        .iter()
+        .find(|label| !is_valid_name(label, false) || label.starts_with("__"))
+    {
+        return Err(format!("invalid label name: {label}").into());
+    }
+    if let Some(label) = labels
+        .iter()
+        .enumerate()
+        .find_map(|(i, label)| labels[..i].contains(label).then_some(label))
+    {
+        return Err(format!("duplicate label name: {label}").into());
+    }
+    let help = help.to_string();
+    let metric = if counter {
+        ScriptMetric::Counter {
+            help,
+            labels,
+            series: AtomicUsize::new(0),
+            family: Family::default(),
+        }
+    } else {
+        ScriptMetric::Gauge {
+            help,
+            labels,
+            series: AtomicUsize::new(0),
+            family: Family::default(),
+        }
+    };
+    metrics.metrics.insert(name.to_string(), metric);
+    Ok(())
+}
+
+/// Checks whether a name is a valid Prometheus metric (`[a-zA-Z_:][a-zA-Z0-9_:]*`) or label (`[a-zA-Z_][a-zA-Z0-9_]*`)
+/// name.
+fn is_valid_name(name: &str, metric: bool) -> bool {
+    let valid = |char: char| char.is_ascii_alphanumeric() || char == '_' || (metric && char == ':');
+    name.chars()
+        .next()
+        .is_some_and(|first| !first.is_ascii_digit() && valid(first))
+        && name.chars().all(valid)
+}
+
+/// [`Update`] is the kind of update that is applied to a scripted metric.
+enum Update {
+    Add,
+    Set,
+}
+
+/// Updates a declared metric. Counters may only be increased and each metric is limited to [`MAX_SERIES`] label sets.
+fn update(
+    metrics: &RwLock<ScriptMetrics>,
+    name: &str,
+    labels: &Map,
+    value: i64,
+    kind: Update,
+) -> Result<(), Box<EvalAltResult>> {
+    let metrics = metrics.read().expect("script metrics poisoned");
+    let Some(metric) = metrics.metrics.get(name) else {
+        return Err(format!("unknown metric: {name}").into());
+    };
+    let labels = metric.labels(labels)?;
+    metric.admit(name, &labels)?;
+
+    match (metric, kind) {
+        (ScriptMetric::Counter { family, .. }, Update::Add) => {
+            let Ok(value) = u64::try_from(value) else {
+                return Err(format!("counter cannot be decreased: {name}").into());
+            };
+            family.get_or_create(&labels).inc_by(value);
+        }
+        (ScriptMetric::Counter { .. }, Update::Set) => {
+            return Err(format!("counter cannot be set: {name}").into());
+        }
+        (ScriptMetric::Gauge { family, .. }, Update::Add) => {
+            family.get_or_create(&labels).inc_by(value);
+        }
+        (ScriptMetric::Gauge { family, .. }, Update::Set) => {
+            family.get_or_create(&labels).set(value);
+        }
+    }
+    Ok(())
+}
+
+/// Converts a Discord id into a script integer. Ids that do not fit into a script integer are passed as unit.
+fn id(id: u64) -> Dynamic {
+    i64::try_from(id).map_or(Dynamic::UNIT, Dynamic::from_int)
+}
+
+/// Creates the script data of a [`Message`]. The category is passed as unit if the channel is not cached.
+fn message_data(ctx: &Context, msg: &Message) -> Option<Map> {
+    let guild_id = msg.guild_id?;
+    let (category_id, channel_id) =
+        find_category_channel(ctx, guild_id, msg.channel_id).unwrap_or((None, msg.channel_id));
+
+    let mut data = Map::new();
+    data.insert("id".into(), id(msg.id.get()));
+    data.insert(
+        "category_id".into(),
+        category_id.map_or(Dynamic::UNIT, |category_id| id(category_id.get())),
+    );
+    data.insert("channel_id".into(), id(channel_id.get()));
+    data.insert("author_id".into(), id(msg.author.id.get()));
+    data.insert("bot".into(), msg.author.bot.into());
+    data.insert("content".into(), msg.content.clone().into());
+    Some(data)
+}
+
+/// Creates the script data of a new [`Member`].
+fn member_data(member: &Member) -> Map {
+    let mut data = Map::new();
+    data.insert("user_id".into(), id(member.user.id.get()));
+    data.insert("name".into(), member.user.name.clone().into());
+    data.insert("bot".into(), member.user.bot.into());
+    data.insert("default_avatar".into(), member.user.avatar.is_none().into());
+    data.insert(
+        "created_at".into(),
+        Dynamic::from_int(member.user.id.created_at().unix_timestamp()),
+    );
+    data
+}
+
+/// Creates the script data of a [`Reaction`]. The category is passed as unit if the channel is not cached.
+fn reaction_data(ctx: &Context, reaction: &Reaction) -> Option<Map> {
+    let guild_id = reaction.guild_id?;
+    let (category_id, channel_id) = find_category_channel(ctx, guild_id, reaction.channel_id)
+        .unwrap_or((None, reaction.channel_id));
+
+    let mut data = Map::new();
+    data.insert("message_id".into(), id(reaction.message_id.get()));
+    data.insert(
+        "category_id".into(),
+        category_id.map_or(Dynamic::UNIT, |category_id| id(category_id.get())),
+    );
+    data.insert("channel_id".into(), id(channel_id.get()));
+    data.insert(
+        "user_id".into(),
+        reaction
+            .user_id
+            .map_or(Dynamic::UNIT, |user_id| id(user_id.get())),
+    );
+    match &reaction.emoji {
+        ReactionType::Custom {
+            id: emoji_id, name, ..
+        } => {
+            data.insert("emoji_id".into(), id(emoji_id.get()));
+            data.insert(
+                "emoji".into(),
+                name.clone().map_or(Dynamic::UNIT, Dynamic::from),
+            );
+        }
+        ReactionType::Unicode(name) => {
+            data.insert("emoji_id".into(), Dynamic::UNIT);
+            data.insert("emoji".into(), name.clone().into());
+        }
+        _ => return None,
+    }
+    Some(data)
+}
+
+#[async_trait]
+impl Collector for ScriptCollector {
+    fn register(&self, registry: &mut Registry) {
+        let metrics = self.metrics.read().expect("script metrics poisoned");
+        let registry = registry.sub_registry_with_prefix(PREFIX);
+        for (name, metric) in &metrics.metrics {
+            debug!(metrics_name = name, "Building script metric");
+            match metric {
+                ScriptMetric::Counter { help, family, .. } => {
+                    registry.register(name, help, family.clone());
+                }
+                ScriptMetric::Gauge { help, family, .. } => {
+                    registry.register(name, help, family.clone());
+                }
+            }
+        }
+    }
+
+    async fn event(&self, ctx: &Context, event: &FullEvent) {
+        let (name, data) = match event {
+            FullEvent::Message { new_message } => ("message", message_data(ctx, new_message)),
+            FullEvent::GuildMemberAddition { new_member } => {
+                ("guild_member_addition", Some(member_data(new_member)))
+            }
+            FullEvent::ReactionAdd { add_reaction } => {
+                ("reaction_add", reaction_data(ctx, add_reaction))
+            }
+            _ => return,
+        };
+        if let Some(data) = data {
+            self.call(name, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads a script from its source.
+    fn load(source: &str) -> Result<ScriptCollector, Box<EvalAltResult>> {
+        let metrics = Arc::new(RwLock::new(ScriptMetrics::default()));
+        let engine = engine(&metrics);
+        let ast = engine.compile(source)?;
+        ScriptCollector::declare(engine, ast, metrics)
+    }
+
+    fn labels(id: i64) -> Map {
+        let mut labels = Map::new();
+        labels.insert("id".into(), id.into());
+        labels
+    }
+
+    #[test]
+    fn declares_metrics() {
+        let collector =
+            load(r#"counter("a", "A", ["id"]); gauge("b", "B", []);"#).expect("expected to load");
+        let metrics = collector.metrics.read().expect("script metrics poisoned");
+        assert!(metrics.loaded);
+        assert_eq!(metrics.metrics.len(), 2);
+    }
+
+    #[test]
+    fn aborts_too_many_operations() {
+        let why = load("loop {}").err().expect("expected to fail");
+        assert!(matches!(*why, EvalAltResult::ErrorTooManyOperations(_)));
+    }
+
+    #[test]
+    fn aborts_too_many_call_levels() {
+        let why = load("fn deeper(n) { deeper(n + 1) } deeper(0);")
+            .err()
+            .expect("expected to fail");
+        assert!(matches!(*why, EvalAltResult::ErrorStackOverflow(_)));
+    }
+
+    #[test]
+    fn rejects_eval() {
+        assert!(load(r#"eval("1 + 1");"#).is_err());
+    }
+
+    #[test]
+    fn rejects_import() {
+        assert!(load(r#"import "other" as other;"#).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        assert!(load(r#"counter("1a", "A", []);"#).is_err());
+        assert!(load(r#"counter("a-b", "A", []);"#).is_err());
+        assert!(load(r#"counter("a", "A", ["__id"]);"#).is_err());
+        assert!(load(r#"counter("a", "A", ["a:b"]);"#).is_err());
+        assert!(load(r#"counter("a", "A", ["id", "id"]);"#).is_err());
+        assert!(load(r#"counter("a:b_c", "A", ["_id"]);"#).is_ok());
+    }
+
+    #[test]
+    fn rejects_declarations_after_load() {
+        let collector = load("").expect("expected to load");
+        assert!(declare(&collector.metrics, "a", "A", Array::new(), true).is_err());
+    }
+
+    #[test]
+    fn rejects_decreasing_counters() {
+        let collector = load(r#"counter("a", "A", []);"#).expect("expected to load");
+        assert!(update(&collector.metrics, "a", &Map::new(), -1, Update::Add).is_err());
+        assert!(update(&collector.metrics, "a", &Map::new(), 5, Update::Set).is_err());
+        assert!(update(&collector.metrics, "a", &Map::new(), 5, Update::Add).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_metrics_and_labels() {
+        let collector = load(r#"gauge("a", "A", ["id"]);"#).expect("expected to load");
+        assert!(update(&collector.metrics, "b", &labels(1), 1, Update::Add).is_err());
+        let mut unknown = Map::new();
+        unknown.insert("other".into(), 1.into());
+        assert!(update(&collector.metrics, "a", &unknown, 1, Update::Add).is_err());
+    }
+
+    #[test]
+    fn rejects_series_beyond_max() {
+        let collector = load(r#"gauge("a", "A", ["id"]);"#).expect("expected to load");
+        for id in 0..MAX_SERIES {
+            let id = i64::try_from(id).expect("expected to fit in i64");
+            update(&collector.metrics, "a", &labels(id), 1, Update::Set)
+                .expect("expected to admit series");
+        }
+        let max = i64::try_from(MAX_SERIES).expect("expected to fit in i64");
+        assert!(update(&collector.metrics, "a", &labels(max), 1, Update::Set).is_err());
+        assert!(update(&collector.metrics, "a", &labels(0), 2, Update::Set).is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+//! This module implements the voice session tracking. A session lasts from joining a voice channel until leaving or
+//! switching the channel.
+
+use serenity::all::ChannelId;
+use std::time::Instant;
+
+/// [`VoiceSession`] is the stay of a member in a single voice channel.
+#[derive(Clone, Debug)]
+pub struct VoiceSession {
+    pub category_id: Option<ChannelId>,
+    pub channel_id: ChannelId,
+    started: Instant,
+    flushed: Instant,
+}
+
+impl VoiceSession {
+    /// Creates a new [`VoiceSession`] that started now.
+    pub fn new(category_id: Option<ChannelId>, channel_id: ChannelId, now: Instant) -> Self {
+        Self {
+            category_id,
+            channel_id,
+            started: now,
+            flushed: now,
+        }
+    }
+
+    /// Takes the seconds of the session that passed since the last flush.
+    pub fn flush(&mut self, now: Instant) -> f64 {
+        let seconds = now.saturating_duration_since(self.flushed).as_secs_f64();
+        self.flushed = now;
+        seconds
+    }
+
+    /// Gets the total duration of the session (in seconds).
+    pub fn duration(&self, now: Instant) -> f64 {
+        now.saturating_duration_since(self.started).as_secs_f64()
+    }
+}